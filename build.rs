@@ -5,6 +5,7 @@ use wayland_scanner::{generate_code, Side};
 
 pub fn main() {
     generate("river_status_unstable_v1");
+    generate("river_control_unstable_v1");
 }
 
 fn generate(protocol_name: &str) {
@@ -0,0 +1,377 @@
+use crate::config::{BadgeStyle, Config};
+use crate::icon::Icon;
+use crate::text::{Font, Mask};
+use snui::widgets::*;
+use snui::*;
+use std::iter;
+use std::rc::Rc;
+
+/// What is drawn inside the squares, indexed by tag and shared by the overlays.
+#[derive(Default)]
+pub struct Contents {
+    pub labels: Vec<Option<Mask>>,
+    pub icons: Vec<Option<Icon>>,
+    /// The badge of a tag holding `n` views is at `n - 1`, the last one stands for the rest.
+    pub badges: Vec<Mask>,
+    /// The height added under the squares by the badges, gap included.
+    pub badge_height: u32,
+}
+
+impl Contents {
+    /// Rasterizes the icons, labels and badges of the tags.
+    ///
+    /// The icons which can't be read are left out,
+    /// as well as the text if the font can't be loaded.
+    pub fn load(config: &Config) -> Contents {
+        let mut contents = Contents::default();
+        contents.icons = (0..32)
+            .map(|tag| {
+                let path = config.per_tag.get(tag)?.icon.as_ref()?;
+                Icon::load(path, config.icons.size)
+                    .map_err(|e| eprintln!("river-tag-overlay: {}, the icon is left out", e))
+                    .ok()
+            })
+            .collect();
+        let labels: Vec<_> = (0..32)
+            .map(|tag| match contents.icons[tag] {
+                Some(_) if !config.icons.labels => None,
+                _ => config.label(tag),
+            })
+            .collect();
+        if labels.iter().any(Option::is_some) {
+            match Font::load(&config.font.family, config.font.size) {
+                Ok(font) => {
+                    contents.labels = labels
+                        .into_iter()
+                        .map(|label| label.map(|label| font.rasterize(&label)))
+                        .collect();
+                }
+                Err(e) => eprintln!("river-tag-overlay: {}, the labels are left out", e),
+            }
+        }
+        let badges = &config.badges;
+        match badges.style {
+            BadgeStyle::None => {}
+            BadgeStyle::Dots => {
+                contents.badges = (1..=badges.max).map(|n| dots(n, badges.dot_size)).collect();
+            }
+            BadgeStyle::Count => match Font::load(&config.font.family, badges.font_size) {
+                Ok(font) => {
+                    contents.badges = (1..=badges.max)
+                        .map(|n| n.to_string())
+                        .chain(iter::once(format!("{}+", badges.max)))
+                        .map(|count| font.rasterize(&count))
+                        .collect();
+                }
+                Err(e) => eprintln!("river-tag-overlay: {}, the badges are left out", e),
+            },
+        }
+        contents.badge_height = contents
+            .badges
+            .iter()
+            .map(|badge| badge.height)
+            .max()
+            .map_or(0, |height| height + badges.gap);
+        contents
+    }
+
+    /// The badge of a tag holding `views` views.
+    fn badge(&self, views: u32) -> Option<&Mask> {
+        let index = (views as usize).min(self.badges.len());
+        index
+            .checked_sub(1)
+            .and_then(|index| self.badges.get(index))
+    }
+}
+
+/// A row of `count` dots of `size` pixels, as far apart as they're wide.
+fn dots(count: u32, size: u32) -> Mask {
+    let width = (count * 2).saturating_sub(1) * size;
+    let mut mask = Mask {
+        width,
+        height: size,
+        coverage: vec![0; (width * size) as usize],
+    };
+    let radius = size as f32 / 2.;
+    for row in 0..size {
+        for col in 0..width {
+            // The distance to the center of the closest dot, the edges are antialiased.
+            let x = (col % (size * 2)) as f32 + 0.5 - radius;
+            let y = row as f32 + 0.5 - radius;
+            let coverage = (radius - x.hypot(y) + 0.5).clamp(0., 1.);
+            mask.coverage[(row * width + col) as usize] = (coverage * 255.) as u8;
+        }
+    }
+    mask
+}
+
+/// How the icon of a cell is drawn.
+#[derive(Clone, Copy, Debug)]
+pub enum Shade {
+    /// The icon keeps its colors and is drawn with this opacity.
+    Opacity(f32),
+    /// The icon is filled with this premultiplied ARGB color.
+    Tint(u32),
+}
+
+/// The gap between an icon and the label under it.
+const GAP: u32 = 2;
+
+/// The colours of what is drawn over and under a square, premultiplied ARGB.
+#[derive(Clone, Copy, Debug)]
+pub struct Palette {
+    pub text: u32,
+    pub badge: u32,
+    pub icon: Shade,
+}
+
+/// The square of a tag with its icon and label, and its badge under it.
+pub struct Cell {
+    square: Rectangle,
+    tag: usize,
+    views: u32,
+    contents: Rc<Contents>,
+    palette: Palette,
+}
+
+impl Cell {
+    pub fn new(
+        size: u32,
+        color: u32,
+        tag: usize,
+        views: u32,
+        contents: Rc<Contents>,
+        palette: Palette,
+    ) -> Cell {
+        Cell {
+            square: Rectangle::square(size, color),
+            tag,
+            views,
+            contents,
+            palette,
+        }
+    }
+}
+
+impl Geometry for Cell {
+    fn get_width(&self) -> u32 {
+        self.square.get_width()
+    }
+    fn get_height(&self) -> u32 {
+        self.square.get_height() + self.contents.badge_height
+    }
+}
+
+impl Drawable for Cell {
+    fn set_color(&mut self, color: u32) {
+        self.square.set_color(color);
+    }
+    fn draw(&self, canvas: &mut [u8], width: u32, x: u32, y: u32) {
+        self.square.draw(canvas, width, x, y);
+        let palette = self.palette;
+        let center = |extent: u32| x as i32 + (self.get_width() as i32 - extent as i32) / 2;
+        if let Some(badge) = self.contents.badge(self.views) {
+            let top = (y + self.get_height() - badge.height) as i32;
+            let origin = (center(badge.width), top);
+            let bounds = (x, y, x + self.get_width(), y + self.get_height());
+            blend(canvas, width, origin, bounds, badge, palette.badge);
+        }
+        let icon = self.contents.icons.get(self.tag).and_then(Option::as_ref);
+        let label = self.contents.labels.get(self.tag).and_then(Option::as_ref);
+        // The icon and the label under it are centered together and clipped to the square.
+        let height = match (icon, label) {
+            (Some(icon), Some(label)) => icon.height + GAP + label.height,
+            (Some(icon), None) => icon.height,
+            (None, Some(label)) => label.height,
+            (None, None) => return,
+        };
+        let bounds = self.bounds(x, y);
+        let mut top = y as i32 + (self.square.get_height() as i32 - height as i32) / 2;
+        if let Some(icon) = icon {
+            let origin = (center(icon.width), top);
+            composite(canvas, width, origin, bounds, icon, palette.icon);
+            top += (icon.height + GAP) as i32;
+        }
+        if let Some(label) = label {
+            let origin = (center(label.width), top);
+            blend(canvas, width, origin, bounds, label, palette.text);
+        }
+    }
+}
+
+impl Widget for Cell {
+    fn damaged(&self) -> bool {
+        self.square.damaged()
+    }
+    fn roundtrip<'d>(
+        &'d mut self,
+        widget_x: u32,
+        widget_y: u32,
+        dispatched: &Dispatch,
+    ) -> Option<Damage<'d>> {
+        self.square.roundtrip(widget_x, widget_y, dispatched)
+    }
+}
+
+impl Cell {
+    /// The area of the canvas covered by the square.
+    fn bounds(&self, x: u32, y: u32) -> (u32, u32, u32, u32) {
+        (x, y, x + self.get_width(), y + self.square.get_height())
+    }
+}
+
+/// Blends a premultiplied ARGB `color` into the canvas through `mask`.
+///
+/// Only the pixels inside `bounds`, given as left, top, right and bottom, are touched.
+pub fn blend(
+    canvas: &mut [u8],
+    width: u32,
+    origin: (i32, i32),
+    bounds: (u32, u32, u32, u32),
+    mask: &Mask,
+    color: u32,
+) {
+    let size = (mask.width, mask.height);
+    let color = color.to_le_bytes();
+    paste(canvas, width, origin, bounds, size, |col, row| {
+        let coverage = mask.coverage[(row * mask.width + col) as usize];
+        scale(color, coverage as u32)
+    });
+}
+
+/// Draws `icon` over the canvas, with its own colors or tinted depending on `shade`.
+///
+/// Only the pixels inside `bounds`, given as left, top, right and bottom, are touched.
+pub fn composite(
+    canvas: &mut [u8],
+    width: u32,
+    origin: (i32, i32),
+    bounds: (u32, u32, u32, u32),
+    icon: &Icon,
+    shade: Shade,
+) {
+    let size = (icon.width, icon.height);
+    paste(canvas, width, origin, bounds, size, |col, row| {
+        let pixel = icon.pixel(col, row);
+        match shade {
+            Shade::Opacity(opacity) => scale(pixel, (opacity * 255.).round() as u32),
+            Shade::Tint(color) => scale(color.to_le_bytes(), pixel[3] as u32),
+        }
+    });
+}
+
+/// Multiplies the channels of a premultiplied pixel by `factor` out of 255.
+fn scale(pixel: [u8; 4], factor: u32) -> [u8; 4] {
+    let mut scaled = pixel;
+    for channel in &mut scaled {
+        *channel = (*channel as u32 * factor / 255) as u8;
+    }
+    scaled
+}
+
+/// Composites the premultiplied pixels given by `source` for an area of `size` at `origin`.
+fn paste(
+    canvas: &mut [u8],
+    width: u32,
+    (left, top): (i32, i32),
+    bounds: (u32, u32, u32, u32),
+    (columns, rows): (u32, u32),
+    source: impl Fn(u32, u32) -> [u8; 4],
+) {
+    for row in 0..rows {
+        for col in 0..columns {
+            let (x, y) = (left + col as i32, top + row as i32);
+            if x < bounds.0 as i32
+                || y < bounds.1 as i32
+                || x >= bounds.2 as i32
+                || y >= bounds.3 as i32
+            {
+                continue;
+            }
+            let source = source(col, row);
+            if source[3] == 0 {
+                continue;
+            }
+            let offset = ((y as u32 * width + x as u32) * 4) as usize;
+            if let Some(pixel) = canvas.get_mut(offset..offset + 4) {
+                let alpha = source[3] as u32;
+                // A source which isn't properly premultiplied could overflow a channel.
+                for (channel, source) in pixel.iter_mut().zip(&source) {
+                    let blended = *source as u32 + *channel as u32 * (255 - alpha) / 255;
+                    *channel = blended.min(255) as u8;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Contents holding the badges of a strip, told apart by their width.
+    fn contents(badges: Vec<Mask>) -> Contents {
+        Contents {
+            badges,
+            ..Contents::default()
+        }
+    }
+
+    /// The width of the badge of a tag holding `views` views.
+    fn badge_width(contents: &Contents, views: u32) -> Option<u32> {
+        contents.badge(views).map(|badge| badge.width)
+    }
+
+    #[test]
+    fn dot_badges_stop_growing_at_the_max() {
+        let contents = contents((1..=3).map(|n| dots(n, 4)).collect());
+        assert_eq!(badge_width(&contents, 0), None);
+        assert_eq!(badge_width(&contents, 1), Some(4));
+        assert_eq!(badge_width(&contents, 3), Some(20));
+        assert_eq!(badge_width(&contents, 4), Some(20));
+        assert_eq!(badge_width(&contents, u32::MAX), Some(20));
+    }
+
+    #[test]
+    fn count_badges_end_with_the_rest() {
+        // The counts 1, 2 and 3, then "3+".
+        let contents = contents(
+            (1..=4)
+                .map(|width| Mask {
+                    width,
+                    height: 1,
+                    coverage: vec![255; width as usize],
+                })
+                .collect(),
+        );
+        assert_eq!(badge_width(&contents, 0), None);
+        assert_eq!(badge_width(&contents, 3), Some(3));
+        assert_eq!(badge_width(&contents, 4), Some(4));
+        assert_eq!(badge_width(&contents, u32::MAX), Some(4));
+    }
+
+    #[test]
+    fn no_badges_without_a_style() {
+        assert_eq!(badge_width(&Contents::default(), 5), None);
+    }
+
+    #[test]
+    fn dots_are_as_far_apart_as_they_are_wide() {
+        assert_eq!(dots(1, 4).width, 4);
+        assert_eq!(dots(0, 4).width, 0);
+        let mask = dots(3, 4);
+        assert_eq!((mask.width, mask.height), (20, 4));
+        assert_eq!(mask.coverage.len(), 80);
+        let coverage = |col: u32, row: u32| mask.coverage[(row * mask.width + col) as usize];
+        for dot in 0..3 {
+            let left = dot * 8;
+            assert_eq!(coverage(left + 1, 1), 255);
+            assert_eq!(coverage(left + 2, 2), 255);
+            // The corners are only partly covered.
+            assert!(coverage(left, 0) > 0 && coverage(left, 0) < 255);
+        }
+        for row in 0..4 {
+            assert!((4..8).chain(12..16).all(|col| coverage(col, row) == 0));
+        }
+    }
+}
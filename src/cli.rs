@@ -0,0 +1,328 @@
+use crate::config::Config;
+use std::path::PathBuf;
+use std::process;
+use std::str::FromStr;
+
+/// Options which don't take a value.
+const SWITCHES: &[&str] = &[
+    "--hide-empty",
+    "--show-on-startup",
+    "--bar",
+    "--focused-only",
+    "--follow-focus",
+    "--pointer",
+    "--skip-empty",
+    "--tint-icons",
+];
+
+/// Options of the original river-tag-overlay without a counterpart, they're accepted
+/// with a warning so its command lines keep working.
+const IGNORED: &[&str] = &[
+    "--square-inner-padding",
+    "--square-border-width",
+    "--square-active-border-colour",
+    "--square-inactive-border-colour",
+    "--square-urgent-border-colour",
+    "--square-active-occupied-colour",
+    "--square-urgent-occupied-colour",
+];
+
+const USAGE: &str = "\
+Usage: river-tag-overlay [options]
+
+  -h, --help                                   Print this help and exit.
+  -v, --version                                Print the version and exit.
+  -c, --config <path>                          Read the configuration from <path>.
+      --tag-amount <int|auto[:<min>]>          Number of tags displayed, up to 32.
+      --hide-empty                             Only show focused, occupied or urgent tags.
+      --square-size <int>                      Side of a tag square in pixels.
+      --square-padding <int>                   Gap between two squares.
+      --padding <int>                          Gap between the squares and the border.
+      --border-width <int>                     Width of the border.
+      --timeout <int>                          Milliseconds before the overlay is hidden.
+      --urgent-timeout <int>                   Milliseconds before an urgency popup is hidden.
+      --extend <bool>                          Whether switching tags restarts the timeout.
+      --show-on-startup                        Show the overlay when the program starts.
+      --bar                                    Never hide the overlay and reserve its space.
+      --fade-in <int>                          Milliseconds the overlay takes to fade in.
+      --fade-out <int>                         Milliseconds the overlay takes to fade out.
+      --slide-in <int>                         Milliseconds the overlay takes to slide in.
+      --easing <easing>                        linear, ease-in, ease-out or ease-in-out.
+      --anchors <position>                     center, top, bottom-left, ... or
+                                               <top>:<right>:<bottom>:<left> with 0 or 1.
+      --margins <top>:<right>:<bottom>:<left>  Margins of the overlay.
+      --layer <layer>                          background, bottom, top or overlay.
+      --namespace <name>                       Namespace of the layer surface.
+      --output <name>                          Only show the overlay on this output.
+      --focused-only                           Only show the overlay on the focused output.
+      --follow-focus                           Show the overlay when an output gains focus.
+      --pointer                                Accept pointer input instead of letting it through.
+      --left-click <command>                   River command run on the clicked tag:
+      --right-click <command>                  set-focused-tags, toggle-focused-tags,
+      --middle-click <command>                 set-view-tags, toggle-view-tags or none.
+      --skip-empty                             Skip the tags without views when scrolling.
+      --labels <labels>                        none, numbers or comma separated names.
+      --font <pattern>                         Fontconfig pattern of the labels' font.
+      --font-size <float>                      Height of the labels in pixels.
+      --icon-size <int>                        Side of the square the icons are scaled into.
+      --tint-icons                             Draw the icons in the colour of the labels.
+      --badges <style>                         none, count or dots under the squares.
+      --badge-max <int>                        Number of views beyond which badges stop growing.
+      --background-colour <colour>
+      --border-colour <colour>
+      --square-inactive-background-colour <colour>
+      --square-inactive-occupied-colour <colour>
+      --square-active-background-colour <colour>
+      --square-urgent-background-colour <colour>
+      --square-inactive-text-colour <colour>
+      --square-occupied-text-colour <colour>
+      --square-active-text-colour <colour>
+      --square-urgent-text-colour <colour>
+      --badge-colour <colour>
+
+Colours are written as #RRGGBB, #RRGGBBAA or 0xRRGGBBAA.
+Options override the values of the configuration file.
+The options --square-inner-padding, --square-border-width, --square-*-border-colour,
+--square-active-occupied-colour and --square-urgent-occupied-colour of the original
+river-tag-overlay are accepted and ignored.
+";
+
+/// The command line arguments.
+///
+/// The flag names follow the ones of the original river-tag-overlay.
+pub struct Args {
+    pub config: Option<PathBuf>,
+    options: Vec<(String, String)>,
+}
+
+impl Args {
+    /// Collects the arguments following the name of the program.
+    ///
+    /// Exits after printing the usage on `--help` or a malformed command line.
+    pub fn new(mut argv: impl Iterator<Item = String>) -> Args {
+        let mut args = Args {
+            config: None,
+            options: Vec::new(),
+        };
+        while let Some(arg) = argv.next() {
+            if arg == "-h" || arg == "--help" {
+                print!("{}", USAGE);
+                process::exit(0);
+            }
+            if arg == "-v" || arg == "--version" {
+                println!("river-tag-overlay {}", env!("CARGO_PKG_VERSION"));
+                process::exit(0);
+            }
+            let (flag, value) = match arg.find('=') {
+                Some(i) if arg.starts_with("--") => {
+                    (arg[..i].to_owned(), Some(arg[i + 1..].to_owned()))
+                }
+                _ => (arg, None),
+            };
+            if !flag.starts_with('-') {
+                usage_error(&format!("unexpected argument \"{}\"", flag));
+            }
+            if SWITCHES.contains(&flag.as_str()) {
+                let value = value.unwrap_or_else(|| "true".to_owned());
+                args.options.push((flag, value));
+                continue;
+            }
+            let value = match value.or_else(|| argv.next()) {
+                Some(value) => value,
+                None => usage_error(&format!("option \"{}\" requires a value", flag)),
+            };
+            if flag == "-c" || flag == "--config" {
+                args.config = Some(PathBuf::from(value));
+            } else {
+                args.options.push((flag, value));
+            }
+        }
+        args
+    }
+
+    /// Overrides the values of the configuration with the ones of the command line.
+    pub fn apply(&self, config: &mut Config) -> Result<(), String> {
+        for (flag, value) in &self.options {
+            match flag.as_str() {
+                "--tag-amount" => config.tags = parse(flag, value)?,
+                "--hide-empty" => config.hide_empty = parse(flag, value)?,
+                "--square-size" => config.size = parse(flag, value)?,
+                "--square-padding" => config.spacing = parse(flag, value)?,
+                "--padding" => config.padding = parse(flag, value)?,
+                "--border-width" => config.border = parse(flag, value)?,
+                "--timeout" => config.timeout = parse(flag, value)?,
+                "--urgent-timeout" => config.urgent_timeout = parse(flag, value)?,
+                "--extend" => config.extend = parse(flag, value)?,
+                "--show-on-startup" => config.show_on_startup = parse(flag, value)?,
+                "--bar" => config.bar = parse(flag, value)?,
+                "--fade-in" => config.animation.fade_in = parse(flag, value)?,
+                "--fade-out" => config.animation.fade_out = parse(flag, value)?,
+                "--slide-in" => config.animation.slide_in = parse(flag, value)?,
+                "--easing" => config.animation.easing = parse(flag, value)?,
+                "--anchors" => config.anchors = parse(flag, value)?,
+                "--margins" => config.margins = parse(flag, value)?,
+                "--layer" => config.layer = parse(flag, value)?,
+                "--namespace" => config.namespace = value.clone(),
+                "--output" => config.output = Some(value.clone()),
+                "--focused-only" => config.focused_only = parse(flag, value)?,
+                "--follow-focus" => config.follow_focus = parse(flag, value)?,
+                "--pointer" => config.pointer = parse(flag, value)?,
+                "--left-click" => config.buttons.left = parse(flag, value)?,
+                "--right-click" => config.buttons.right = parse(flag, value)?,
+                "--middle-click" => config.buttons.middle = parse(flag, value)?,
+                "--skip-empty" => config.skip_empty = parse(flag, value)?,
+                "--labels" => config.labels = parse(flag, value)?,
+                "--font" => config.font.family = value.clone(),
+                "--font-size" => config.font.size = parse(flag, value)?,
+                "--icon-size" => config.icons.size = parse(flag, value)?,
+                "--tint-icons" => config.icons.tint = parse(flag, value)?,
+                "--badges" => config.badges.style = parse(flag, value)?,
+                "--badge-max" => config.badges.max = parse(flag, value)?,
+                "--background-colour" => config.colors.background = parse(flag, value)?,
+                "--border-colour" => config.colors.border = parse(flag, value)?,
+                "--square-inactive-background-colour" => {
+                    config.colors.square.inactive = Some(parse(flag, value)?)
+                }
+                "--square-inactive-occupied-colour" => {
+                    config.colors.square.occupied = Some(parse(flag, value)?)
+                }
+                "--square-active-background-colour" => {
+                    config.colors.square.focused = Some(parse(flag, value)?)
+                }
+                "--square-urgent-background-colour" => {
+                    config.colors.square.urgent = Some(parse(flag, value)?)
+                }
+                "--square-inactive-text-colour" => {
+                    config.colors.text.inactive = Some(parse(flag, value)?)
+                }
+                "--square-occupied-text-colour" => {
+                    config.colors.text.occupied = Some(parse(flag, value)?)
+                }
+                "--square-active-text-colour" => {
+                    config.colors.text.focused = Some(parse(flag, value)?)
+                }
+                "--square-urgent-text-colour" => {
+                    config.colors.text.urgent = Some(parse(flag, value)?)
+                }
+                "--badge-colour" => config.colors.badge = parse(flag, value)?,
+                flag if IGNORED.contains(&flag) => {
+                    eprintln!("river-tag-overlay: option \"{}\" is ignored", flag)
+                }
+                _ => return Err(format!("unknown option \"{}\"", flag)),
+            }
+        }
+        Ok(())
+    }
+}
+
+fn parse<T>(flag: &str, value: &str) -> Result<T, String>
+where
+    T: FromStr,
+    T::Err: ToString,
+{
+    value
+        .parse()
+        .map_err(|e: T::Err| format!("{}: {}", flag, e.to_string()))
+}
+
+pub fn usage_error(msg: &str) -> ! {
+    eprintln!("river-tag-overlay: {}\n\n{}", msg, USAGE);
+    process::exit(1);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tags::Style;
+
+    /// Applies a command line to `config`.
+    fn apply(argv: &[&str], config: &mut Config) -> Result<Args, String> {
+        let args = Args::new(argv.iter().map(|arg| arg.to_string()));
+        args.apply(config)?;
+        Ok(args)
+    }
+
+    #[test]
+    fn values_follow_the_flag_or_an_equal_sign() {
+        let mut config = Config::default();
+        apply(
+            &["--square-size=40", "--padding", "6", "--labels=a=b"],
+            &mut config,
+        )
+        .unwrap();
+        assert_eq!(config.size, 40);
+        assert_eq!(config.padding, 6);
+        assert_eq!(config.labels.label(0).as_deref(), Some("a=b"));
+    }
+
+    #[test]
+    fn switches_can_be_turned_off() {
+        let mut config = Config {
+            bar: true,
+            ..Config::default()
+        };
+        apply(
+            &["--hide-empty", "--bar=false", "--tint-icons=true"],
+            &mut config,
+        )
+        .unwrap();
+        assert!(config.hide_empty);
+        assert!(!config.bar);
+        assert!(config.icons.tint);
+        assert!(apply(&["--bar=maybe"], &mut config).is_err());
+    }
+
+    #[test]
+    fn upstream_flags_are_ignored() {
+        let mut config = Config::default();
+        apply(
+            &[
+                "--square-inner-padding",
+                "3",
+                "--square-active-border-colour=#ffffff",
+                "--square-urgent-occupied-colour",
+                "#ffffff",
+            ],
+            &mut config,
+        )
+        .unwrap();
+        assert_eq!(format!("{:?}", config), format!("{:?}", Config::default()));
+    }
+
+    #[test]
+    fn flags_override_the_configuration() {
+        let mut config: Config = toml::from_str(
+            "size = 20
+            timeout = 100
+            [colors.square]
+            focused = \"#a3be8c\"",
+        )
+        .unwrap();
+        let args = apply(
+            &[
+                "--config=overlay.toml",
+                "--timeout",
+                "500",
+                "--square-active-background-colour",
+                "#c6aa82",
+            ],
+            &mut config,
+        )
+        .unwrap();
+        assert_eq!(args.config, Some(PathBuf::from("overlay.toml")));
+        assert_eq!(config.size, 20);
+        assert_eq!(config.timeout, 500);
+        assert_eq!(
+            config.colors.square(Style::Focused),
+            "#c6aa82".parse().unwrap()
+        );
+    }
+
+    #[test]
+    fn invalid_values_and_unknown_flags_are_errors() {
+        let mut config = Config::default();
+        let error = apply(&["--square-size", "big"], &mut config).err().unwrap();
+        assert!(error.starts_with("--square-size: "), "{}", error);
+        assert!(apply(&["--square-colour", "#ffffff"], &mut config).is_err());
+    }
+}
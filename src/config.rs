@@ -0,0 +1,1146 @@
+use crate::error::Error;
+use crate::tags::Style;
+use serde::Deserialize;
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use wayland_protocols::wlr::unstable::layer_shell::v1::client::{
+    zwlr_layer_shell_v1, zwlr_layer_surface_v1,
+};
+
+/// Deserializes types written as strings through their `FromStr` implementation.
+macro_rules! deserialize_from_str {
+    ($($ty:ty),*) => {$(
+        impl<'de> Deserialize<'de> for $ty {
+            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+            where
+                D: serde::Deserializer<'de>,
+            {
+                let s = String::deserialize(deserializer)?;
+                s.parse().map_err(serde::de::Error::custom)
+            }
+        }
+    )*};
+}
+
+deserialize_from_str!(Color, Anchors, Layer, BadgeStyle, Easing, TagCommand);
+
+/// A colour stored in the premultiplied ARGB format snui expects.
+///
+/// It can be written as `#RRGGBB`, `#RRGGBBAA` or `0xRRGGBBAA`, with a straight alpha.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Color(pub u32);
+
+impl Color {
+    /// The colour with all its channels scaled by `opacity`.
+    ///
+    /// Buffers hold premultiplied colours so fading scales the colour along with the alpha.
+    pub fn faded(self, opacity: f32) -> Color {
+        if opacity >= 1. {
+            return self;
+        }
+        let opacity = opacity.max(0.);
+        let channel = |shift: u32| {
+            let value = (self.0 >> shift & 0xff) as f32 * opacity;
+            (value.round() as u32) << shift
+        };
+        Color(channel(24) | channel(16) | channel(8) | channel(0))
+    }
+}
+
+impl FromStr for Color {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let hex = s
+            .strip_prefix('#')
+            .or_else(|| s.strip_prefix("0x"))
+            .ok_or_else(|| format!("invalid colour \"{}\": expected #RRGGBB[AA]", s))?;
+        let value = u32::from_str_radix(hex, 16)
+            .map_err(|_| format!("invalid colour \"{}\": not a hexadecimal value", s))?;
+        match hex.len() {
+            6 => Ok(Color(0xff00_0000 | value)),
+            8 => {
+                let alpha = (value & 0xff) as f32 / 255.;
+                Ok(Color(0xff00_0000 | value >> 8).faded(alpha))
+            }
+            _ => Err(format!("invalid colour \"{}\": expected #RRGGBB[AA]", s)),
+        }
+    }
+}
+
+/// A value for each style of tag.
+///
+/// The settings which can be left out hold options, the defaults are applied by their users.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct PerStyle<T> {
+    pub inactive: T,
+    pub occupied: T,
+    pub focused: T,
+    pub urgent: T,
+}
+
+impl<T: Copy> PerStyle<T> {
+    pub fn get(&self, style: Style) -> T {
+        match style {
+            Style::Focused => self.focused,
+            Style::Urgent => self.urgent,
+            Style::Occupied => self.occupied,
+            Style::Inactive => self.inactive,
+        }
+    }
+}
+
+/// The colours of the squares when the configuration leaves them out.
+const SQUARE: PerStyle<Color> = PerStyle {
+    inactive: Color(0xff33_3232),
+    occupied: Color(0xff98_967e),
+    focused: Color(0xffc6_aa82),
+    urgent: Color(0xffbf_616a),
+};
+
+/// The colours of the labels when the configuration leaves them out.
+const TEXT: PerStyle<Color> = PerStyle {
+    inactive: Color(0xff98_967e),
+    occupied: Color(0xff26_2525),
+    focused: Color(0xff26_2525),
+    urgent: Color(0xff26_2525),
+};
+
+/// The opacity of the icons when the configuration leaves it out.
+const ICON_OPACITY: PerStyle<f32> = PerStyle {
+    inactive: 0.6,
+    occupied: 1.,
+    focused: 1.,
+    urgent: 1.,
+};
+
+/// Splits a `<top>:<right>:<bottom>:<left>` quadruplet.
+fn edges<T: FromStr>(s: &str) -> Option<[T; 4]> {
+    let mut values = s.split(':').map(|v| v.trim().parse().ok());
+    let edges = [
+        values.next()??,
+        values.next()??,
+        values.next()??,
+        values.next()??,
+    ];
+    match values.next() {
+        Some(_) => None,
+        None => Some(edges),
+    }
+}
+
+/// Edges of the output the overlay is anchored to.
+///
+/// Written as a position (`center`, `top`, `bottom-left`, ...) or as
+/// `<top>:<right>:<bottom>:<left>` where each edge is `0` or `1`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Anchors {
+    pub top: bool,
+    pub right: bool,
+    pub bottom: bool,
+    pub left: bool,
+}
+
+impl FromStr for Anchors {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let [top, right, bottom, left] = match s {
+            "center" => [0, 0, 0, 0],
+            "top" => [1, 0, 0, 0],
+            "right" => [0, 1, 0, 0],
+            "bottom" => [0, 0, 1, 0],
+            "left" => [0, 0, 0, 1],
+            "top-left" => [1, 0, 0, 1],
+            "top-right" => [1, 1, 0, 0],
+            "bottom-left" => [0, 0, 1, 1],
+            "bottom-right" => [0, 1, 1, 0],
+            _ => edges::<u8>(s)
+                .filter(|edges| edges.iter().all(|edge| *edge <= 1))
+                .ok_or_else(|| {
+                    format!(
+                        "invalid anchors \"{}\": expected a position or <top>:<right>:<bottom>:<left>",
+                        s
+                    )
+                })?,
+        };
+        Ok(Anchors {
+            top: top == 1,
+            right: right == 1,
+            bottom: bottom == 1,
+            left: left == 1,
+        })
+    }
+}
+
+impl Anchors {
+    /// Whether the overlay is anchored to a single edge, alone or stretched along it
+    /// by the perpendicular edges, which is required to reserve space.
+    pub fn single_edge(&self) -> bool {
+        let edges = [self.top, self.right, self.bottom, self.left];
+        matches!(edges.iter().filter(|edge| **edge).count(), 1 | 3)
+    }
+}
+
+impl From<Anchors> for zwlr_layer_surface_v1::Anchor {
+    fn from(anchors: Anchors) -> Self {
+        let mut anchor = zwlr_layer_surface_v1::Anchor::empty();
+        if anchors.top {
+            anchor |= zwlr_layer_surface_v1::Anchor::Top;
+        }
+        if anchors.right {
+            anchor |= zwlr_layer_surface_v1::Anchor::Right;
+        }
+        if anchors.bottom {
+            anchor |= zwlr_layer_surface_v1::Anchor::Bottom;
+        }
+        if anchors.left {
+            anchor |= zwlr_layer_surface_v1::Anchor::Left;
+        }
+        anchor
+    }
+}
+
+/// Number of tags displayed, river has 32 of them.
+///
+/// Written as a number or as `auto`, optionally followed by `:<min>`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TagCount {
+    Fixed(u32),
+    /// Shows at least this many tags and the higher ones which are
+    /// focused, occupied or urgent.
+    Auto(u32),
+}
+
+impl TagCount {
+    /// Number of tags shown for the given focused, occupied and urgent tags.
+    pub fn count(self, tags: u32) -> u32 {
+        match self {
+            TagCount::Fixed(count) => count,
+            TagCount::Auto(min) => min.max(32 - tags.leading_zeros()),
+        }
+    }
+}
+
+impl FromStr for TagCount {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("invalid tag amount \"{}\": expected 1 to 32 or auto", s);
+        let (auto, count) = match s.strip_prefix("auto") {
+            Some("") => return Ok(TagCount::Auto(9)),
+            Some(min) => (true, min.strip_prefix(':').ok_or_else(invalid)?),
+            None => (false, s),
+        };
+        let count = count.parse().ok().filter(|count| (1..=32).contains(count));
+        match (auto, count) {
+            (false, Some(count)) => Ok(TagCount::Fixed(count)),
+            (true, Some(min)) => Ok(TagCount::Auto(min)),
+            (_, None) => Err(invalid()),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for TagCount {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Value {
+            Number(u32),
+            String(String),
+        }
+        let s = match Value::deserialize(deserializer)? {
+            Value::Number(n) => n.to_string(),
+            Value::String(s) => s,
+        };
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct Margins {
+    pub top: i32,
+    pub right: i32,
+    pub bottom: i32,
+    pub left: i32,
+}
+
+impl FromStr for Margins {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let [top, right, bottom, left] = edges(s).ok_or_else(|| {
+            format!(
+                "invalid margins \"{}\": expected <top>:<right>:<bottom>:<left>",
+                s
+            )
+        })?;
+        Ok(Margins {
+            top,
+            right,
+            bottom,
+            left,
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Layer {
+    Background,
+    Bottom,
+    Top,
+    Overlay,
+}
+
+impl FromStr for Layer {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "background" => Ok(Layer::Background),
+            "bottom" => Ok(Layer::Bottom),
+            "top" => Ok(Layer::Top),
+            "overlay" => Ok(Layer::Overlay),
+            _ => Err(format!(
+                "invalid layer \"{}\": expected background, bottom, top or overlay",
+                s
+            )),
+        }
+    }
+}
+
+impl From<Layer> for zwlr_layer_shell_v1::Layer {
+    fn from(layer: Layer) -> Self {
+        match layer {
+            Layer::Background => zwlr_layer_shell_v1::Layer::Background,
+            Layer::Bottom => zwlr_layer_shell_v1::Layer::Bottom,
+            Layer::Top => zwlr_layer_shell_v1::Layer::Top,
+            Layer::Overlay => zwlr_layer_shell_v1::Layer::Overlay,
+        }
+    }
+}
+
+/// Colours replacing the ones of `[colors]` for a single tag.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TagColors {
+    pub square: PerStyle<Option<Color>>,
+    pub text: PerStyle<Option<Color>>,
+    pub badge: Option<Color>,
+}
+
+/// The settings of a single tag, the n-th `[[tag]]` table applies to the n-th tag.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TagConfig {
+    /// Replaces the label given by `labels`.
+    pub label: Option<String>,
+    /// A PNG or SVG image drawn in the square, relative to the configuration file.
+    pub icon: Option<PathBuf>,
+    pub colors: TagColors,
+}
+
+/// The text drawn in the squares.
+///
+/// Written as `none`, `numbers` or a list of names, the names can be separated by commas
+/// on the command line.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Labels {
+    None,
+    /// The number of the tag, counting from 1.
+    Numbers,
+    /// The names of the tags, the tags without a name are left blank.
+    Names(Vec<String>),
+}
+
+impl Labels {
+    /// The label of the tag at `index`, counting from 0.
+    pub fn label(&self, index: usize) -> Option<String> {
+        match self {
+            Labels::None => None,
+            Labels::Numbers => Some((index + 1).to_string()),
+            Labels::Names(names) => names.get(index).filter(|name| !name.is_empty()).cloned(),
+        }
+    }
+}
+
+impl FromStr for Labels {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "none" => Ok(Labels::None),
+            "numbers" => Ok(Labels::Numbers),
+            _ => Ok(Labels::Names(
+                s.split(',').map(|name| name.trim().to_owned()).collect(),
+            )),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Labels {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Value {
+            Names(Vec<String>),
+            String(String),
+        }
+        match Value::deserialize(deserializer)? {
+            Value::Names(names) => Ok(Labels::Names(names)),
+            Value::String(s) if s == "none" || s == "numbers" => Ok(s.parse().unwrap()),
+            Value::String(s) => Err(serde::de::Error::custom(format!(
+                "invalid labels \"{}\": expected none, numbers or a list of names",
+                s
+            ))),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Font {
+    /// A fontconfig pattern, like `monospace` or `Fira Sans:bold`.
+    pub family: String,
+    /// Height of the text in pixels.
+    pub size: f32,
+}
+
+impl Default for Font {
+    fn default() -> Self {
+        Font {
+            family: "monospace".to_owned(),
+            size: 16.,
+        }
+    }
+}
+
+/// How the icons of the `[[tag]]` tables are drawn.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Icons {
+    /// Side of the square the icons are scaled into, in pixels.
+    pub size: u32,
+    /// Icons are drawn in the text color of the tag, only their alpha is kept.
+    pub tint: bool,
+    /// The label of a tag is drawn under its icon instead of being left out.
+    pub labels: bool,
+    /// From 0 to 1 for each style of tag.
+    pub opacity: PerStyle<Option<f32>>,
+}
+
+impl Default for Icons {
+    fn default() -> Self {
+        Icons {
+            size: 24,
+            tint: false,
+            labels: false,
+            opacity: PerStyle::default(),
+        }
+    }
+}
+
+impl Icons {
+    pub fn opacity(&self, style: Style) -> f32 {
+        self.opacity
+            .get(style)
+            .unwrap_or_else(|| ICON_OPACITY.get(style))
+    }
+}
+
+/// How the number of views on a tag is shown under its square.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BadgeStyle {
+    None,
+    /// The number of views, as text.
+    Count,
+    /// A dot per view.
+    Dots,
+}
+
+impl FromStr for BadgeStyle {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "none" => Ok(BadgeStyle::None),
+            "count" => Ok(BadgeStyle::Count),
+            "dots" => Ok(BadgeStyle::Dots),
+            _ => Err(format!(
+                "invalid badge style \"{}\": expected none, count or dots",
+                s
+            )),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Badges {
+    pub style: BadgeStyle,
+    /// Views beyond this number add no dot and are counted as `<max>+`.
+    pub max: u32,
+    /// Diameter of the dots in pixels.
+    pub dot_size: u32,
+    /// Height of the count in pixels, in the font of the labels.
+    pub font_size: f32,
+    /// The gap between a square and its badge.
+    pub gap: u32,
+}
+
+impl Default for Badges {
+    fn default() -> Self {
+        Badges {
+            style: BadgeStyle::None,
+            max: 5,
+            dot_size: 4,
+            font_size: 10.,
+            gap: 3,
+        }
+    }
+}
+
+/// How the progress of an animation is distributed over its duration.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Easing {
+    Linear,
+    EaseIn,
+    EaseOut,
+    EaseInOut,
+}
+
+impl Easing {
+    /// Maps the elapsed part of an animation, from 0 to 1, to its progress.
+    pub fn apply(self, t: f32) -> f32 {
+        match self {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t * t,
+            Easing::EaseOut => 1. - (1. - t).powi(3),
+            Easing::EaseInOut if t < 0.5 => 4. * t * t * t,
+            Easing::EaseInOut => 1. - (-2. * t + 2.).powi(3) / 2.,
+        }
+    }
+}
+
+impl FromStr for Easing {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "linear" => Ok(Easing::Linear),
+            "ease-in" => Ok(Easing::EaseIn),
+            "ease-out" => Ok(Easing::EaseOut),
+            "ease-in-out" => Ok(Easing::EaseInOut),
+            _ => Err(format!(
+                "invalid easing \"{}\": expected linear, ease-in, ease-out or ease-in-out",
+                s
+            )),
+        }
+    }
+}
+
+/// Durations of the animations in milliseconds, 0 disables them.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Animation {
+    pub fade_in: u64,
+    pub fade_out: u64,
+    /// The overlay slides in from the edge it's anchored to.
+    pub slide_in: u64,
+    pub easing: Easing,
+}
+
+impl Default for Animation {
+    fn default() -> Self {
+        Animation {
+            fade_in: 0,
+            fade_out: 0,
+            slide_in: 0,
+            easing: Easing::EaseOut,
+        }
+    }
+}
+
+/// A river command run on the tag under the pointer.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TagCommand {
+    SetFocusedTags,
+    ToggleFocusedTags,
+    SetViewTags,
+    ToggleViewTags,
+    /// Does nothing.
+    None,
+}
+
+impl TagCommand {
+    const ALL: [TagCommand; 5] = [
+        TagCommand::SetFocusedTags,
+        TagCommand::ToggleFocusedTags,
+        TagCommand::SetViewTags,
+        TagCommand::ToggleViewTags,
+        TagCommand::None,
+    ];
+
+    /// The name of the river command, taking the tags as its argument.
+    pub fn name(self) -> Option<&'static str> {
+        match self {
+            TagCommand::SetFocusedTags => Some("set-focused-tags"),
+            TagCommand::ToggleFocusedTags => Some("toggle-focused-tags"),
+            TagCommand::SetViewTags => Some("set-view-tags"),
+            TagCommand::ToggleViewTags => Some("toggle-view-tags"),
+            TagCommand::None => None,
+        }
+    }
+}
+
+impl FromStr for TagCommand {
+    type Err = String;
+
+    /// Parses the name of the river command, or `none`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let written = |command: &TagCommand| command.name().unwrap_or("none");
+        TagCommand::ALL
+            .iter()
+            .find(|command| written(command) == s)
+            .copied()
+            .ok_or_else(|| {
+                let names: Vec<_> = TagCommand::ALL.iter().map(written).collect();
+                format!("invalid command \"{}\": expected {}", s, names.join(", "))
+            })
+    }
+}
+
+/// The commands bound to the buttons of the pointer.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Buttons {
+    pub left: TagCommand,
+    pub right: TagCommand,
+    pub middle: TagCommand,
+}
+
+impl Default for Buttons {
+    fn default() -> Self {
+        Buttons {
+            left: TagCommand::SetFocusedTags,
+            right: TagCommand::ToggleFocusedTags,
+            middle: TagCommand::SetViewTags,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Colors {
+    pub background: Color,
+    pub border: Color,
+    pub square: PerStyle<Option<Color>>,
+    pub text: PerStyle<Option<Color>>,
+    pub badge: Color,
+}
+
+impl Colors {
+    /// The colour of a square.
+    pub fn square(&self, style: Style) -> Color {
+        self.square.get(style).unwrap_or_else(|| SQUARE.get(style))
+    }
+
+    /// The colour of the label of a square.
+    pub fn text(&self, style: Style) -> Color {
+        self.text.get(style).unwrap_or_else(|| TEXT.get(style))
+    }
+}
+
+impl Default for Colors {
+    fn default() -> Self {
+        Colors {
+            background: Color(0xff26_2525),
+            border: Color(0xff33_3232),
+            square: PerStyle::default(),
+            text: PerStyle::default(),
+            badge: Color(0xff98_967e),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Number of tags displayed.
+    pub tags: TagCount,
+    /// Only show the tags which are focused, occupied or urgent.
+    pub hide_empty: bool,
+    /// Side of a tag square in pixels.
+    pub size: u32,
+    /// Gap between two squares.
+    pub spacing: u32,
+    /// Gap between the squares and the border.
+    pub padding: u32,
+    /// Width of the border around the overlay.
+    pub border: u32,
+    /// Time in milliseconds before the overlay is hidden.
+    pub timeout: u64,
+    /// Time in milliseconds before the overlay brought up by an urgent tag is hidden.
+    pub urgent_timeout: u64,
+    /// Switching tags while the overlay is visible restarts the timeout.
+    pub extend: bool,
+    /// Show the overlay for the focused and urgent tags river sends when the program starts.
+    pub show_on_startup: bool,
+    /// Keep the overlay visible as a bar reserving its space on the output.
+    pub bar: bool,
+    pub animation: Animation,
+    pub labels: Labels,
+    pub font: Font,
+    pub icons: Icons,
+    pub badges: Badges,
+    pub anchors: Anchors,
+    pub margins: Margins,
+    pub layer: Layer,
+    /// Namespace of the layer surface, compositors may apply rules to it.
+    pub namespace: String,
+    /// Name of the only output the overlay is shown on.
+    pub output: Option<String>,
+    /// Only show the overlay on the output focused by the seat.
+    pub focused_only: bool,
+    /// Show the overlay on an output when it gains focus.
+    pub follow_focus: bool,
+    /// Accept pointer input, the overlay lets clicks through otherwise.
+    pub pointer: bool,
+    pub buttons: Buttons,
+    /// Scrolling over the overlay skips the tags without views.
+    pub skip_empty: bool,
+    pub colors: Colors,
+    #[serde(rename = "tag")]
+    pub per_tag: Vec<TagConfig>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            tags: TagCount::Fixed(9),
+            hide_empty: false,
+            size: 40,
+            spacing: 10,
+            padding: 10,
+            border: 1,
+            timeout: 500,
+            urgent_timeout: 2000,
+            extend: true,
+            show_on_startup: false,
+            bar: false,
+            animation: Animation::default(),
+            labels: Labels::Numbers,
+            font: Font::default(),
+            icons: Icons::default(),
+            badges: Badges::default(),
+            anchors: Anchors::default(),
+            margins: Margins::default(),
+            layer: Layer::Overlay,
+            namespace: "overlay".to_owned(),
+            output: None,
+            focused_only: false,
+            follow_focus: false,
+            pointer: false,
+            buttons: Buttons::default(),
+            skip_empty: false,
+            colors: Colors::default(),
+            per_tag: Vec::new(),
+        }
+    }
+}
+
+impl Config {
+    /// Location of the configuration file.
+    ///
+    /// `$XDG_CONFIG_HOME/river-tag-overlay/config.toml`, falling back to `$HOME/.config`.
+    pub fn path() -> Option<PathBuf> {
+        let config_home = env::var_os("XDG_CONFIG_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+        Some(config_home.join("river-tag-overlay").join("config.toml"))
+    }
+
+    /// Validates what the types of the fields can't.
+    ///
+    /// It runs once the command line is applied, which may bring invalid values too.
+    pub fn check(&self) -> Result<(), String> {
+        if self.per_tag.len() > 32 {
+            return Err(format!(
+                "{} [[tag]] tables given, river has 32 tags",
+                self.per_tag.len()
+            ));
+        }
+        let styles = [
+            Style::Inactive,
+            Style::Occupied,
+            Style::Focused,
+            Style::Urgent,
+        ];
+        if styles
+            .iter()
+            .any(|style| !(0. ..=1.).contains(&self.icons.opacity(*style)))
+        {
+            return Err("the opacities of [icons] must be between 0 and 1".to_owned());
+        }
+        // The default anchors of a bar are replaced by the top edge.
+        if self.bar && self.anchors != Anchors::default() && !self.anchors.single_edge() {
+            return Err(
+                "a bar must be anchored to a single edge, like top, bottom, left or right"
+                    .to_owned(),
+            );
+        }
+        if self.badges.style != BadgeStyle::None && self.badges.max == 0 {
+            return Err("the badge max must be at least 1".to_owned());
+        }
+        let sizes = [
+            ("font size", self.font.size),
+            ("icon size", self.icons.size as f32),
+            ("badge font size", self.badges.font_size),
+            ("badge dot size", self.badges.dot_size as f32),
+        ];
+        if let Some((name, size)) = sizes.iter().find(|(_, size)| size.is_nan() || *size <= 0.) {
+            return Err(format!("the {} must be positive, got {}", name, size));
+        }
+        Ok(())
+    }
+
+    /// The label of the tag at `index`, counting from 0.
+    pub fn label(&self, index: usize) -> Option<String> {
+        self.per_tag
+            .get(index)
+            .and_then(|tag| tag.label.clone())
+            .or_else(|| self.labels.label(index))
+    }
+
+    /// The colour of the square of a tag, after the overrides of its `[[tag]]` table.
+    pub fn square_color(&self, index: usize, style: Style) -> Color {
+        let colors = self.per_tag.get(index).map(|tag| &tag.colors);
+        colors
+            .and_then(|colors| colors.square.get(style))
+            .unwrap_or_else(|| self.colors.square(style))
+    }
+
+    /// The colour of the label of a tag, after the overrides of its `[[tag]]` table.
+    pub fn text_color(&self, index: usize, style: Style) -> Color {
+        let colors = self.per_tag.get(index).map(|tag| &tag.colors);
+        colors
+            .and_then(|colors| colors.text.get(style))
+            .unwrap_or_else(|| self.colors.text(style))
+    }
+
+    /// The colour of the badge of a tag, after the override of its `[[tag]]` table.
+    pub fn badge_color(&self, index: usize) -> Color {
+        let colors = self.per_tag.get(index).map(|tag| &tag.colors);
+        colors
+            .and_then(|colors| colors.badge)
+            .unwrap_or(self.colors.badge)
+    }
+
+    /// Reads the configuration file at `path` or the default location.
+    ///
+    /// A missing file at the default location isn't an error, the defaults are used instead.
+    pub fn load(path: Option<&Path>) -> Result<Config, Error> {
+        let explicit = path.is_some();
+        let path = match path.map(Path::to_path_buf).or_else(Config::path) {
+            Some(path) => path,
+            None => return Ok(Config::default()),
+        };
+        match fs::read_to_string(&path) {
+            Ok(content) => match toml::from_str::<Config>(&content) {
+                Ok(mut config) => {
+                    // Relative icon paths start from the directory of the file.
+                    let dir = path.parent().unwrap_or_else(|| Path::new(""));
+                    for tag in &mut config.per_tag {
+                        if let Some(icon) = tag.icon.as_mut() {
+                            *icon = dir.join(&*icon);
+                        }
+                    }
+                    Ok(config)
+                }
+                Err(e) => Err(Error::Config {
+                    path,
+                    message: e.to_string(),
+                }),
+            },
+            Err(e) if !explicit && e.kind() == io::ErrorKind::NotFound => Ok(Config::default()),
+            Err(e) => Err(Error::Config {
+                path,
+                message: e.to_string(),
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Parses a configuration file.
+    fn config(toml: &str) -> Config {
+        toml::from_str(toml).unwrap()
+    }
+
+    #[test]
+    fn colors() {
+        assert_eq!("#c6aa82".parse(), Ok(Color(0xffc6_aa82)));
+        // The alpha is written last but stored first, and multiplies the other channels.
+        assert_eq!("#c6aa8280".parse(), Ok(Color(0x8063_5541)));
+        assert_eq!("0xc6aa8280".parse(), Ok(Color(0x8063_5541)));
+        assert_eq!("#c6aa82ff".parse(), Ok(Color(0xffc6_aa82)));
+        assert_eq!("#c6aa8200".parse(), Ok(Color(0)));
+        assert!("c6aa82".parse::<Color>().is_err());
+        assert!("#c6aa8".parse::<Color>().is_err());
+        assert!("#c6aa8g".parse::<Color>().is_err());
+        assert_eq!(
+            config("[colors]\nborder = \"#c6aa82\"").colors.border,
+            Color(0xffc6_aa82)
+        );
+    }
+
+    #[test]
+    fn faded_colors_stay_premultiplied() {
+        assert_eq!(Color(0xff80_4020).faded(0.5), Color(0x8040_2010));
+        assert_eq!(Color(0xff80_4020).faded(1.), Color(0xff80_4020));
+        assert_eq!(Color(0xff80_4020).faded(-1.), Color(0));
+    }
+
+    #[test]
+    fn square_colors_fall_back_to_the_defaults() {
+        let config = config("[colors.square]\nfocused = \"#a3be8c\"");
+        assert_eq!(config.colors.square(Style::Focused), Color(0xffa3_be8c));
+        assert_eq!(config.colors.square(Style::Urgent), SQUARE.urgent);
+        assert!(toml::from_str::<Config>("[colors]\nfocused = \"#a3be8c\"").is_err());
+    }
+
+    #[test]
+    fn tag_counts() {
+        assert_eq!("9".parse(), Ok(TagCount::Fixed(9)));
+        assert_eq!("32".parse(), Ok(TagCount::Fixed(32)));
+        assert_eq!("auto".parse(), Ok(TagCount::Auto(9)));
+        assert_eq!("auto:4".parse(), Ok(TagCount::Auto(4)));
+        for invalid in &["0", "33", "auto:", "auto:0", "auto4", "nine"] {
+            assert!(invalid.parse::<TagCount>().is_err(), "{}", invalid);
+        }
+        assert_eq!(config("tags = 5").tags, TagCount::Fixed(5));
+        assert_eq!(config("tags = \"auto:3\"").tags, TagCount::Auto(3));
+    }
+
+    #[test]
+    fn auto_tag_count_grows_to_the_highest_used_tag() {
+        assert_eq!(TagCount::Fixed(9).count(1 << 20), 9);
+        assert_eq!(TagCount::Auto(9).count(0), 9);
+        assert_eq!(TagCount::Auto(9).count(0b1_0000_0001), 9);
+        assert_eq!(TagCount::Auto(9).count(1 << 11 | 1), 12);
+        assert_eq!(TagCount::Auto(4).count(1 << 4), 5);
+        assert_eq!(TagCount::Auto(4).count(1 << 31), 32);
+    }
+
+    #[test]
+    fn anchors() {
+        let anchors = |top, right, bottom, left| Anchors {
+            top,
+            right,
+            bottom,
+            left,
+        };
+        assert_eq!("center".parse(), Ok(Anchors::default()));
+        assert_eq!("top".parse(), Ok(anchors(true, false, false, false)));
+        assert_eq!("bottom-left".parse(), Ok(anchors(false, false, true, true)));
+        assert_eq!("1:0:0:1".parse(), Ok(anchors(true, false, false, true)));
+        assert_eq!(
+            " 0 : 1 :0:0".parse(),
+            Ok(anchors(false, true, false, false))
+        );
+        for invalid in &["middle", "1:0:0", "1:0:0:0:0", "2:0:0:0", "a:b:c:d"] {
+            assert!(invalid.parse::<Anchors>().is_err(), "{}", invalid);
+        }
+        assert_eq!(
+            config("anchors = \"top\"").anchors,
+            anchors(true, false, false, false)
+        );
+    }
+
+    #[test]
+    fn margins() {
+        assert_eq!(
+            "10:-5:0:3".parse(),
+            Ok(Margins {
+                top: 10,
+                right: -5,
+                bottom: 0,
+                left: 3,
+            })
+        );
+        assert!("10:5:0".parse::<Margins>().is_err());
+        assert!("10:5:0:x".parse::<Margins>().is_err());
+        assert_eq!(config("margins = { top = 4 }").margins.top, 4);
+    }
+
+    #[test]
+    fn layers() {
+        assert_eq!("overlay".parse(), Ok(Layer::Overlay));
+        assert!("above".parse::<Layer>().is_err());
+        assert_eq!(config("layer = \"bottom\"").layer, Layer::Bottom);
+    }
+
+    #[test]
+    fn single_edge_anchors() {
+        let single = |s: &str| s.parse::<Anchors>().unwrap().single_edge();
+        assert!(single("top") && single("left") && single("1:1:0:1"));
+        assert!(!single("center") && !single("top-left"));
+        assert!(!single("1:0:1:0") && !single("1:1:1:1"));
+    }
+
+    #[test]
+    fn bars_need_an_edge_to_reserve_space_on() {
+        let bar = |anchors: &str| Config {
+            bar: true,
+            anchors: anchors.parse().unwrap(),
+            ..Config::default()
+        };
+        assert!(bar("center").check().is_ok());
+        assert!(bar("bottom").check().is_ok());
+        assert!(bar("top-left").check().is_err());
+        assert!(Config {
+            anchors: "top-left".parse().unwrap(),
+            ..Config::default()
+        }
+        .check()
+        .is_ok());
+    }
+
+    #[test]
+    fn labels() {
+        assert_eq!("none".parse(), Ok(Labels::None));
+        assert_eq!("numbers".parse(), Ok(Labels::Numbers));
+        let names: Labels = "web, code,,chat".parse().unwrap();
+        assert_eq!(names.label(0).as_deref(), Some("web"));
+        assert_eq!(names.label(1).as_deref(), Some("code"));
+        assert_eq!(names.label(2), None);
+        assert_eq!(names.label(3).as_deref(), Some("chat"));
+        assert_eq!(names.label(4), None);
+        assert_eq!(Labels::Numbers.label(8).as_deref(), Some("9"));
+        assert_eq!(Labels::None.label(0), None);
+
+        assert_eq!(config("labels = \"none\"").labels, Labels::None);
+        assert_eq!(
+            config("labels = [\"web\"]").labels,
+            Labels::Names(vec!["web".to_owned()])
+        );
+        assert!(toml::from_str::<Config>("labels = \"web\"").is_err());
+    }
+
+    #[test]
+    fn text_colors_fall_back_to_the_defaults() {
+        let config = config("[colors.text]\ninactive = \"#a3be8c\"");
+        assert_eq!(config.colors.text(Style::Inactive), Color(0xffa3_be8c));
+        assert_eq!(config.colors.text(Style::Focused), TEXT.focused);
+    }
+
+    #[test]
+    fn fonts_need_a_size() {
+        let font = |size| Config {
+            font: Font {
+                size,
+                ..Font::default()
+            },
+            ..Config::default()
+        };
+        assert!(font(10.).check().is_ok());
+        assert!(font(0.).check().is_err());
+        assert!(font(f32::NAN).check().is_err());
+    }
+
+    #[test]
+    fn per_tag_overrides() {
+        let config = config(
+            "labels = \"numbers\"
+            [[tag]]
+            label = \"web\"
+            colors = { square = { focused = \"#a3be8c\" }, text = { urgent = \"#ffffff\" } }
+            [[tag]]
+            [[tag]]
+            label = \"chat\"",
+        );
+        assert_eq!(config.label(0).as_deref(), Some("web"));
+        assert_eq!(config.label(1).as_deref(), Some("2"));
+        assert_eq!(config.label(2).as_deref(), Some("chat"));
+        assert_eq!(config.label(3).as_deref(), Some("4"));
+        assert_eq!(config.square_color(0, Style::Focused), Color(0xffa3_be8c));
+        assert_eq!(
+            config.square_color(0, Style::Urgent),
+            config.colors.square(Style::Urgent)
+        );
+        assert_eq!(
+            config.square_color(1, Style::Focused),
+            config.colors.square(Style::Focused)
+        );
+        assert_eq!(config.text_color(0, Style::Urgent), Color(0xffff_ffff));
+        assert_eq!(
+            config.text_color(0, Style::Focused),
+            config.colors.text(Style::Focused)
+        );
+    }
+
+    #[test]
+    fn unknown_keys_are_rejected() {
+        assert!(toml::from_str::<Config>("size = 40\nsquare_size = 40").is_err());
+        assert!(toml::from_str::<Config>("[[tag]]\ncolors = { border = \"#ffffff\" }").is_err());
+    }
+
+    #[test]
+    fn river_has_32_tags_to_configure() {
+        let tags = |count| Config {
+            per_tag: vec![TagConfig::default(); count],
+            ..Config::default()
+        };
+        assert!(tags(32).check().is_ok());
+        assert!(tags(33).check().is_err());
+    }
+
+    #[test]
+    fn badges() {
+        assert_eq!("dots".parse(), Ok(BadgeStyle::Dots));
+        assert!("bars".parse::<BadgeStyle>().is_err());
+        let config = config(
+            "[colors]
+            badge = \"#ffffff\"
+            [badges]
+            style = \"count\"
+            max = 9
+            [[tag]]
+            colors = { badge = \"#a3be8c\" }",
+        );
+        assert_eq!(config.badges.style, BadgeStyle::Count);
+        assert_eq!(config.badges.max, 9);
+        assert_eq!(config.badge_color(0), Color(0xffa3_be8c));
+        assert_eq!(config.badge_color(1), Color(0xffff_ffff));
+    }
+
+    #[test]
+    fn shown_badges_need_a_max() {
+        let badges = |style, max| Config {
+            badges: Badges {
+                style,
+                max,
+                ..Badges::default()
+            },
+            ..Config::default()
+        };
+        assert!(badges(BadgeStyle::Dots, 1).check().is_ok());
+        assert!(badges(BadgeStyle::Count, 0).check().is_err());
+        assert!(badges(BadgeStyle::None, 0).check().is_ok());
+    }
+}
@@ -0,0 +1,63 @@
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+use wayland_client::{ConnectError, ProtocolError};
+
+pub enum Error {
+    /// The Wayland socket couldn't be reached.
+    Connect(ConnectError),
+    /// A global required by the overlay isn't advertised by the compositor.
+    MissingGlobal(&'static str),
+    /// The compositor killed the connection.
+    Protocol(ProtocolError),
+    Io(io::Error),
+    Config {
+        path: PathBuf,
+        message: String,
+    },
+    /// A value of the configuration file or the command line is out of range.
+    Invalid(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Connect(e) => write!(f, "failed to connect to the wayland compositor: {}", e),
+            Error::MissingGlobal("zriver_status_manager_v1") => write!(
+                f,
+                "compositor does not support river-status-unstable-v1 (are you running river?)"
+            ),
+            Error::MissingGlobal("zwlr_layer_shell_v1") => {
+                write!(f, "compositor does not support wlr-layer-shell-unstable-v1")
+            }
+            Error::MissingGlobal(interface) => {
+                write!(f, "compositor does not advertise {}", interface)
+            }
+            Error::Protocol(e) => write!(f, "the compositor closed the connection: {}", e),
+            Error::Io(e) => write!(f, "wayland connection error: {}", e),
+            Error::Config { path, message } => write!(f, "{}: {}", path.display(), message),
+            Error::Invalid(message) => write!(f, "invalid configuration: {}", message),
+        }
+    }
+}
+
+// `main` reports the error it returns with its Debug implementation.
+impl fmt::Debug for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<ConnectError> for Error {
+    fn from(e: ConnectError) -> Self {
+        Error::Connect(e)
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Io(e)
+    }
+}
@@ -0,0 +1,68 @@
+use smithay_client_toolkit::reexports::calloop::channel::Sender;
+use wayland_client::protocol::{wl_output::WlOutput, wl_seat::WlSeat};
+
+/// The messages handled by the event loop.
+///
+/// Overlays are identified by the protocol id of their output.
+pub enum Event {
+    OutputAdded(WlOutput),
+    OutputRemoved(WlOutput),
+    /// The seat focused an output.
+    OutputFocused {
+        output: u32,
+        /// Whether it's the output focused when the seat status was bound.
+        initial: bool,
+    },
+    OutputUnfocused(u32),
+    /// The focused tags of an output changed.
+    TagsChanged {
+        output: u32,
+        focused: u32,
+    },
+    /// The views of an output changed, `tags` is the `view_tags` array.
+    ViewsChanged {
+        output: u32,
+        tags: Vec<u8>,
+    },
+    UrgentChanged {
+        output: u32,
+        urgent: u32,
+    },
+    /// Shows the overlay of an output until its timeout expires.
+    Show {
+        output: u32,
+        /// Whether a tag became urgent, the urgent timeout applies.
+        urgent: bool,
+    },
+    Hide(u32),
+    /// The layer surface of an overlay was configured.
+    Configure {
+        output: u32,
+        serial: u32,
+    },
+    /// The compositor is ready for the next frame of an overlay's animation.
+    Frame(u32),
+    /// The compositor closed the layer surface of an overlay.
+    Closed(u32),
+    /// A button was pressed over the surface with this id.
+    Click {
+        seat: WlSeat,
+        surface: u32,
+        x: f64,
+        y: f64,
+        button: u32,
+    },
+    /// The wheel moved over the surface with this id, positive steps scroll down.
+    Scroll {
+        seat: WlSeat,
+        surface: u32,
+        steps: i32,
+    },
+}
+
+/// Sends an event from a Wayland callback to the event loop.
+pub fn send(sender: &Sender<Event>, event: Event) {
+    if sender.send(event).is_err() {
+        eprintln!("river-tag-overlay: the event loop stopped, an event was dropped");
+    }
+}
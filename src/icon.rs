@@ -0,0 +1,66 @@
+use std::fs;
+use std::path::Path;
+use tiny_skia::{FilterQuality, Pixmap, PixmapPaint, Transform};
+
+/// A rasterized image, premultiplied RGBA.
+pub struct Icon {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+impl Icon {
+    /// Rasterizes a PNG or SVG file to fit a square of `size` pixels, keeping its aspect ratio.
+    pub fn load(path: &Path, size: u32) -> Result<Icon, String> {
+        let error = |e: &dyn std::fmt::Display| format!("{}: {}", path.display(), e);
+        let data = fs::read(path).map_err(|e| error(&e))?;
+        let svg = matches!(
+            path.extension().and_then(|ext| ext.to_str()),
+            Some("svg") | Some("svgz")
+        );
+        let pixmap = if svg {
+            let tree = usvg::Tree::from_data(&data, &usvg::Options::default().to_ref())
+                .map_err(|e| error(&e))?;
+            let fit = usvg::FitTo::Size(size, size);
+            let fitted = fit
+                .fit_to(tree.svg_node().size.to_screen_size())
+                .ok_or_else(|| error(&"empty image"))?;
+            let mut pixmap = Pixmap::new(fitted.width(), fitted.height())
+                .ok_or_else(|| error(&"empty image"))?;
+            resvg::render(&tree, fit, Transform::default(), pixmap.as_mut())
+                .ok_or_else(|| error(&"failed to render"))?;
+            pixmap
+        } else {
+            let image = Pixmap::decode_png(&data).map_err(|e| error(&e))?;
+            let scale = size as f32 / image.width().max(image.height()) as f32;
+            let width = (image.width() as f32 * scale).round().max(1.) as u32;
+            let height = (image.height() as f32 * scale).round().max(1.) as u32;
+            let mut pixmap = Pixmap::new(width, height).ok_or_else(|| error(&"empty image"))?;
+            let paint = PixmapPaint {
+                quality: FilterQuality::Bicubic,
+                ..PixmapPaint::default()
+            };
+            pixmap.draw_pixmap(
+                0,
+                0,
+                image.as_ref(),
+                &paint,
+                Transform::from_scale(scale, scale),
+                None,
+            );
+            pixmap
+        };
+        Ok(Icon {
+            width: pixmap.width(),
+            height: pixmap.height(),
+            pixels: pixmap.take(),
+        })
+    }
+
+    /// The color of the pixel at `col` and `row` as little-endian premultiplied ARGB.
+    pub fn pixel(&self, col: u32, row: u32) -> [u8; 4] {
+        let offset = ((row * self.width + col) * 4) as usize;
+        let rgba = &self.pixels[offset..offset + 4];
+        [rgba[2], rgba[1], rgba[0], rgba[3]]
+    }
+}
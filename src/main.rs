@@ -1,214 +1,551 @@
+mod cell;
+mod cli;
+mod config;
+mod error;
+mod event;
+mod icon;
+mod overlay;
+mod pointer;
+mod tags;
+mod text;
 mod wayland;
 
-use snui::*;
-use std::time;
-use std::thread;
-use snui::widgets::*;
-use snui::wayland::app;
-use std::sync::mpsc::Sender;
-use wayland_client::{Display, Proxy};
+use crate::wayland::river_control_unstable_v1::zriver_command_callback_v1;
+use crate::wayland::river_control_unstable_v1::zriver_control_v1::ZriverControlV1;
 use crate::wayland::river_status_unstable_v1::zriver_output_status_v1;
+use crate::wayland::river_status_unstable_v1::zriver_seat_status_v1::{self, ZriverSeatStatusV1};
 use crate::wayland::river_status_unstable_v1::zriver_status_manager_v1::ZriverStatusManagerV1;
-use wayland_protocols::wlr::unstable::layer_shell::v1::client::zwlr_layer_shell_v1::Layer;
+use cell::Contents;
+use cli::Args;
+use config::{Config, TagCommand};
+use error::Error;
+use event::Event;
+use overlay::Overlay;
+use std::env;
+use std::rc::Rc;
+use wayland_client::{Attached, DispatchData, Display, Interface, Main, Proxy};
+use wayland_protocols::unstable::xdg_output::v1::client::zxdg_output_manager_v1::ZxdgOutputManagerV1;
 use wayland_protocols::wlr::unstable::layer_shell::v1::client::zwlr_layer_shell_v1::ZwlrLayerShellV1;
-use wayland_protocols::wlr::unstable::layer_shell::v1::client::zwlr_layer_surface_v1;
 
 use wayland_client::protocol::{
-    wl_compositor::WlCompositor, wl_output::WlOutput, wl_shm::WlShm, wl_surface::WlSurface,
+    wl_compositor::WlCompositor, wl_output::WlOutput, wl_pointer::WlPointer, wl_seat::WlSeat,
+    wl_shm::WlShm,
 };
 
 use smithay_client_toolkit::{
     environment,
-    environment::{Environment, SimpleGlobal},
-    output::OutputHandler,
+    environment::{Environment, GlobalHandler, SimpleGlobal},
+    output::{
+        with_output_info, OutputHandler, OutputHandling, OutputInfo, OutputStatusListener,
+        XdgOutputHandler,
+    },
+    reexports::calloop::{
+        channel::{self, Sender},
+        timer::{Timer, TimerHandle},
+        EventLoop,
+    },
+    seat::{with_seat_data, SeatData, SeatHandler},
     shm::ShmHandler,
+    WaylandSource,
 };
 
 pub struct Env {
     status_manager: SimpleGlobal<ZriverStatusManagerV1>,
+    control: SimpleGlobal<ZriverControlV1>,
     compositor: SimpleGlobal<WlCompositor>,
     layer_shell: SimpleGlobal<ZwlrLayerShellV1>,
     outputs: OutputHandler,
+    xdg_output: XdgOutputHandler,
+    seats: SeatHandler,
     shm: ShmHandler,
 }
 
 impl Env {
     fn new() -> Env {
+        let (outputs, xdg_output) = XdgOutputHandler::new_output_handlers();
         Env {
             status_manager: SimpleGlobal::new(),
+            control: SimpleGlobal::new(),
             compositor: SimpleGlobal::new(),
             layer_shell: SimpleGlobal::new(),
-            outputs: OutputHandler::new(),
+            outputs,
+            xdg_output,
+            seats: SeatHandler::new(),
             shm: ShmHandler::new(),
         }
     }
 }
 
+impl OutputHandling for Env {
+    fn listen<F: FnMut(WlOutput, &OutputInfo, DispatchData) + 'static>(
+        &mut self,
+        f: F,
+    ) -> OutputStatusListener {
+        self.outputs.listen(f)
+    }
+}
+
 environment!(Env,
     singles = [
         ZriverStatusManagerV1 => status_manager,
+        ZriverControlV1 => control,
         ZwlrLayerShellV1 => layer_shell,
+        ZxdgOutputManagerV1 => xdg_output,
            WlCompositor => compositor,
            WlShm => shm,
     ],
     multis=[
         WlOutput => outputs,
+        WlSeat => seats,
     ]
 );
 
-const BG0: u32 = 0xff_26_25_25;
-const BG1: u32 = 0xff_33_32_32;
-const GRN: u32 = 0xff_98_96_7E;
-const YEL: u32 = 0xff_c6_aa_82;
-
-struct TagsData {
-    focused: u32,
-    views: Vec<u32>,
-}
+fn main() -> Result<(), Error> {
+    let args = Args::new(env::args().skip(1));
+    let mut config = Config::load(args.config.as_deref())?;
+    if let Err(e) = args.apply(&mut config) {
+        cli::usage_error(&e);
+    }
+    config.check().map_err(Error::Invalid)?;
 
-fn main() {
-    let display = Display::connect_to_env().unwrap();
+    let display = Display::connect_to_env()?;
     let mut event_queue = display.create_event_queue();
     let wl_display = Proxy::clone(&display).attach(event_queue.token());
-    let env = Environment::new(&wl_display, &mut event_queue, Env::new()).unwrap();
-
-    let widget = create_widget(9, 40);
-    let mut mempool = env.create_auto_pool().unwrap();
-    let shm = env.require_global::<WlShm>();
-    let status_manager = env.require_global::<ZriverStatusManagerV1>();
-    let draw = mempool
-        .resize((widget.get_width() * widget.get_height() * 4) as usize)
-        .is_ok();
-
-    let surface = env.create_surface();
-	let display_handle = display.clone();
-    let (app, mut sender) = app::Application::new(widget, surface.detach(), shm.detach());
-    thread::spawn(|| {
-        let mut state = 0;
-        app.run(display_handle, |app, pool, dispatch| match dispatch {
-                Dispatch::Data(name, mut data) => match name {
-                    "tagdata" => if let Some(tags) = data.as_mut().downcast_mut::<TagsData>() {
-                        for w in &mut app.widget.widget.widget.widgets.iter_mut() {
-                            w.widget.set_color(BG1);
-                        }
-                        for t in &tags.views {
-                            if *t < 9 {
-                                app.widget.widget.widget.widgets[*t as usize].widget.set_color(GRN);
-                            }
-                        }
-                        for (i, w) in &mut app.widget.widget.widget.widgets.iter_mut().enumerate() {
-                            let tagmask = 1 << i;
-                            if tagmask == tags.focused {
-                                w.widget.set_color(YEL);
-                            } else if tagmask != tags.focused && (tags.focused / tagmask) % 2 != 0 {
-                                w.widget.set_color(YEL);
-                                tags.focused -= 1 << i;
-                            }
-                        }
-                    },
-                    "swap" => {
-                        if let Some((surface, layer_surface)) = data.as_ref()
-                        	.downcast_ref::<(WlSurface, zwlr_layer_surface_v1::ZwlrLayerSurfaceV1)>() {
-                                if state == 0 {
-                                	app.destroy();
-                                	app.surface = surface.clone();
-                                	app.layer_surface = Some(layer_surface.clone());
-                                    layer_surface.set_size(app.widget.get_width(), app.widget.get_height());
-                                    surface.commit();
-                                } else {
-                                    app.render(pool);
-                                    app.show();
-                                }
-                                state += 1;
-                        }
-                    }
-                    _ => {}
-                }
-                Dispatch::Message(msg) => if msg == "hide" {
-                    if state > 1 {
-                        state -= 1;
-                    } else {
-                        app.hide();
-                        state -= 1;
-                    }
-                }
-                Dispatch::Commit => if state > 0 {
-                    app.init(pool);
-                }
-                _ => {}
+    let env = Environment::new(&wl_display, &mut event_queue, Env::new())?;
+    // The names of the outputs are sent once the xdg_output objects are created.
+    event_queue.sync_roundtrip(&mut (), |_, _, _| {})?;
+
+    let status_manager = require_global::<ZriverStatusManagerV1>(&env)?;
+    require_global::<ZwlrLayerShellV1>(&env)?;
+    require_global::<WlCompositor>(&env)?;
+    require_global::<WlShm>(&env)?;
+
+    let mut event_loop = EventLoop::<State>::try_new()?;
+    let handle = event_loop.handle();
+
+    // Every event of the Wayland callbacks goes through this channel.
+    let (sender, channel) = channel::channel::<Event>();
+    handle
+        .insert_source(channel, |event, _, state| {
+            if let channel::Event::Msg(event) = event {
+                state.handle(event);
             }
-        );
-    });
+        })
+        .map_err(|e| e.error)?;
 
-    if draw {
-        for output in env.get_all_outputs() {
-            let compositor = env.require_global::<WlCompositor>();
-            let layer_shell = env.require_global::<ZwlrLayerShellV1>();
-            let output_status = status_manager.get_river_output_status(&output);
-
-            let mut viewstag: Vec<u32> = Vec::new();
-            output_status.quick_assign(move |_, event, mut sender| {
-                if let Some(sender) = sender.get::<Sender<Dispatch>>() {
-                    match event {
-                        zriver_output_status_v1::Event::FocusedTags { tags } => {
-                            let tagdata = TagsData {
-                                focused: tags,
-                                views: viewstag.clone()
-                            };
-                            if sender.send(Dispatch::Data("tagdata", Box::new(tagdata))).is_ok() {
-                                let surface = compositor.create_surface();
-                                let layer_surface = layer_shell
-                                    .get_layer_surface(&surface, None, Layer::Overlay, "overlay".to_owned());
-                                surface.quick_assign(|_, _, _| {});
-                                app::assign_layer_surface(&surface, &layer_surface);
-                                sender.send(Dispatch::Data("swap", Box::new((surface.detach(), layer_surface.detach())))).unwrap();
-                                let handle = sender.clone();
-                                thread::spawn(move || {
-                                    thread::sleep(time::Duration::from_millis(500));
-                                    if let Err(e) = handle.send(Dispatch::Message("hide")) {
-                                        eprintln!("{}", e);
-                                    }
-                                });
-                            }
-                        }
-                        zriver_output_status_v1::Event::ViewTags { tags } => {
-                            viewstag = tags[0..]
-                                .chunks(4)
-                                .map(|s| {
-                                    let buf = [s[0], s[1], s[2], s[3]];
-                                    u32::from_le_bytes(buf)
-                                })
-                                .collect();
-                        }
-                        zriver_output_status_v1::Event::UrgentTags { tags } => {
-                            sender.send(Dispatch::Data("urgent", Box::new(tags))).unwrap();
-                        }
-                    }
-                }
-            });
-        }
-    }
+    // The hide timeouts of the overlays, identified by their id.
+    let timer = Timer::<u32>::new()?;
+    let timer_handle = timer.handle();
+    handle
+        .insert_source(timer, |id, _, state| {
+            state.handle(Event::Hide(id));
+        })
+        .map_err(|e| e.error)?;
 
-    loop {
-        event_queue
-            .dispatch(&mut sender, |event, object, _| {
-                panic!(
-                    "[callop] Encountered an orphan event: {}@{}: {}",
+    handle
+        .insert_source(WaylandSource::new(event_queue), |(), queue, state| {
+            queue.dispatch_pending(state, |event, object, _| {
+                eprintln!(
+                    "Ignoring an orphan event: {}@{}: {}",
                     event.interface,
                     object.as_ref().id(),
                     event.name
                 );
             })
-            .unwrap();
+        })
+        .map_err(|e| e.error)?;
+
+    let mut state = State {
+        env: env.clone(),
+        overlays: Vec::new(),
+        focused_output: None,
+        sender: sender.clone(),
+        timer: timer_handle,
+        control: env.get_global::<ZriverControlV1>(),
+        contents: Rc::new(Contents::load(&config)),
+        config,
+    };
+    if state.config.pointer && state.control.is_none() {
+        eprintln!("compositor does not support river-control-unstable-v1, clicks are ignored");
+    }
+
+    for output in env.get_all_outputs() {
+        state.add_output(output)?;
     }
+
+    // Seats and their pointers can show up after startup.
+    let mut seats = Vec::new();
+    let use_pointer = state.config.pointer;
+    for seat in env.get_all_seats() {
+        if let Some(data) = with_seat_data(&seat, SeatData::clone) {
+            seat_changed(
+                &mut seats,
+                &seat,
+                &data,
+                &status_manager,
+                &sender,
+                use_pointer,
+            );
+        }
+    }
+    let seat_sender = sender.clone();
+    let _seat_listener = env.listen_for_seats(move |seat, data, _| {
+        seat_changed(
+            &mut seats,
+            &seat,
+            data,
+            &status_manager,
+            &seat_sender,
+            use_pointer,
+        );
+    });
+
+    // Keeps the output listener alive for the whole session.
+    let _listener = env.listen_for_outputs(move |output, info, _| {
+        let event = if info.obsolete {
+            Event::OutputRemoved(output)
+        } else {
+            Event::OutputAdded(output)
+        };
+        event::send(&sender, event);
+    });
+
+    loop {
+        // Requests sent outside of the Wayland callbacks aren't flushed by the source.
+        display.flush()?;
+        if let Err(e) = event_loop.dispatch(None, &mut state) {
+            return Err(match display.protocol_error() {
+                Some(e) => Error::Protocol(e),
+                None => Error::Io(e),
+            });
+        }
+    }
+}
+
+/// The objects bound for a seat.
+struct Seat {
+    seat: WlSeat,
+    status: Main<ZriverSeatStatusV1>,
+    /// Only bound when the pointer input is enabled.
+    pointer: Option<Main<WlPointer>>,
+}
+
+/// Binds the status of a new seat, and its pointer when it gains one.
+///
+/// The objects of a removed seat or of a pointer which goes away are released.
+fn seat_changed(
+    seats: &mut Vec<Seat>,
+    seat: &Attached<WlSeat>,
+    data: &SeatData,
+    status_manager: &Attached<ZriverStatusManagerV1>,
+    sender: &Sender<Event>,
+    use_pointer: bool,
+) {
+    let index = match seats.iter().position(|known| known.seat == **seat) {
+        Some(index) => index,
+        None if data.defunct => return,
+        None => {
+            seats.push(Seat {
+                seat: seat.detach(),
+                status: bind_seat_status(status_manager, seat, sender.clone()),
+                pointer: None,
+            });
+            seats.len() - 1
+        }
+    };
+    let known = &mut seats[index];
+    if use_pointer && data.has_pointer && !data.defunct {
+        if known.pointer.is_none() {
+            let pointer = seat.get_pointer();
+            pointer::assign(&pointer, seat.detach(), sender.clone());
+            known.pointer = Some(pointer);
+        }
+    } else if let Some(pointer) = known.pointer.take() {
+        if pointer.as_ref().version() >= 3 {
+            pointer.release();
+        }
+    }
+    if data.defunct {
+        seats.remove(index).status.destroy();
+    }
+}
+
+/// Forwards the outputs focused by a seat to the event loop.
+fn bind_seat_status(
+    status_manager: &Attached<ZriverStatusManagerV1>,
+    seat: &WlSeat,
+    sender: Sender<Event>,
+) -> Main<ZriverSeatStatusV1> {
+    let seat_status = status_manager.get_river_seat_status(seat);
+    // River sends the focused output as soon as the seat status is bound.
+    let mut initial = true;
+    seat_status.quick_assign(move |_, event, _| {
+        let event = match event {
+            zriver_seat_status_v1::Event::FocusedOutput { output } => {
+                let event = Event::OutputFocused {
+                    output: output.as_ref().id(),
+                    initial,
+                };
+                initial = false;
+                event
+            }
+            zriver_seat_status_v1::Event::UnfocusedOutput { output } => {
+                Event::OutputUnfocused(output.as_ref().id())
+            }
+            _ => return,
+        };
+        event::send(&sender, event);
+    });
+    seat_status
+}
+
+/// Gets a global or reports which one the compositor is missing.
+fn require_global<I: Interface>(env: &Environment<Env>) -> Result<Attached<I>, Error>
+where
+    Env: GlobalHandler<I>,
+{
+    env.get_global::<I>().ok_or(Error::MissingGlobal(I::NAME))
 }
 
-fn create_widget(amount: u32, icon_size: u32) -> Border<Background<WidgetLayout>> {
-    let mut tags = WidgetLayout::horizontal(10);
+/// The data shared by the sources of the event loop.
+struct State {
+    env: Environment<Env>,
+    config: Config,
+    overlays: Vec<Overlay>,
+    /// The id of the output focused by the seat.
+    focused_output: Option<u32>,
+    sender: Sender<Event>,
+    timer: TimerHandle<u32>,
+    control: Option<Attached<ZriverControlV1>>,
+    contents: Rc<Contents>,
+}
+
+impl State {
+    /// Creates the overlay of a new output.
+    fn add_output(&mut self, output: WlOutput) -> Result<(), Error> {
+        if self.overlays.iter().any(|overlay| overlay.output == output) {
+            return Ok(());
+        }
+        if let Some(name) = &self.config.output {
+            if with_output_info(&output, |info| &info.name != name).unwrap_or(true) {
+                return Ok(());
+            }
+        }
+        let overlay = Overlay::new(
+            &self.env,
+            &self.config,
+            output,
+            self.sender.clone(),
+            self.timer.clone(),
+            self.contents.clone(),
+        )?;
+        let output = overlay.id();
+        let sender = self.sender.clone();
+        overlay.output_status.quick_assign(move |_, event, _| {
+            let event = match event {
+                zriver_output_status_v1::Event::FocusedTags { tags } => Event::TagsChanged {
+                    output,
+                    focused: tags,
+                },
+                zriver_output_status_v1::Event::ViewTags { tags } => {
+                    Event::ViewsChanged { output, tags }
+                }
+                zriver_output_status_v1::Event::UrgentTags { tags } => Event::UrgentChanged {
+                    output,
+                    urgent: tags,
+                },
+            };
+            event::send(&sender, event);
+        });
+        self.overlays.push(overlay);
+        Ok(())
+    }
+
+    /// Destroys the overlay of an output which went away.
+    fn remove_output(&mut self, output: &WlOutput) {
+        let id = output.as_ref().id();
+        for overlay in self
+            .overlays
+            .iter_mut()
+            .filter(|overlay| overlay.id() == id)
+        {
+            overlay.destroy();
+        }
+        self.overlays.retain(|overlay| overlay.id() != id);
+        if self.focused_output == Some(id) {
+            self.focused_output = None;
+        }
+        if output.as_ref().version() >= 3 {
+            output.release();
+        }
+    }
+
+    /// Runs a river command taking a tag bitfield as its argument.
+    fn run_tag_command(&self, seat: &WlSeat, command: TagCommand, tags: u32) {
+        let (control, name) = match (&self.control, command.name()) {
+            (Some(control), Some(name)) => (control, name),
+            _ => return,
+        };
+        control.add_argument(name.to_owned());
+        control.add_argument(tags.to_string());
+        control.run_command(seat).quick_assign(|_, event, _| {
+            if let zriver_command_callback_v1::Event::Failure { failure_message } = event {
+                eprintln!("river: {}", failure_message);
+            }
+        });
+    }
 
-    for _ in 0..amount {
-        tags.add(Rectangle::square(icon_size, BG1)).unwrap();
+    fn overlay(&mut self, id: u32) -> Option<&mut Overlay> {
+        self.overlays.iter_mut().find(|overlay| overlay.id() == id)
     }
 
-    boxed(tags, 10, 1, BG0, BG1)
+    fn handle(&mut self, event: Event) {
+        match event {
+            Event::OutputAdded(output) => {
+                if let Err(e) = self.add_output(output) {
+                    eprintln!("{}", e);
+                }
+            }
+            Event::OutputRemoved(output) => self.remove_output(&output),
+            Event::OutputFocused {
+                output: id,
+                initial,
+            } => {
+                self.focused_output = Some(id);
+                if self.config.follow_focus && (!initial || self.config.show_on_startup) {
+                    self.handle(Event::Show {
+                        output: id,
+                        urgent: false,
+                    });
+                }
+            }
+            Event::OutputUnfocused(id) => {
+                if self.focused_output == Some(id) {
+                    self.focused_output = None;
+                }
+            }
+            Event::TagsChanged { output, focused } => {
+                if let Some(overlay) = self.overlay(output) {
+                    // River sends the focused tags as soon as the output status is bound.
+                    let startup = !overlay.tags.focus_known;
+                    overlay.tags.set_focused(focused);
+                    overlay.update();
+                    if overlay.tags.focus_changed() && (!startup || self.config.show_on_startup) {
+                        self.handle(Event::Show {
+                            output,
+                            urgent: false,
+                        });
+                    } else {
+                        overlay.redraw();
+                    }
+                }
+            }
+            Event::ViewsChanged { output, tags } => {
+                if let Some(overlay) = self.overlay(output) {
+                    overlay.tags.set_view_tags(&tags);
+                    overlay.update();
+                    overlay.redraw();
+                }
+            }
+            Event::UrgentChanged { output, urgent } => {
+                if let Some(overlay) = self.overlay(output) {
+                    // Only a tag becoming urgent brings up the overlay, river sends the
+                    // urgent tags as soon as the output status is bound.
+                    let startup = !overlay.tags.urgent_known;
+                    let raised = overlay.tags.set_urgent(urgent);
+                    overlay.update();
+                    if raised != 0 && (!startup || self.config.show_on_startup) {
+                        self.handle(Event::Show {
+                            output,
+                            urgent: true,
+                        });
+                    } else {
+                        overlay.redraw();
+                    }
+                }
+            }
+            Event::Show { output, urgent } => {
+                let filtered = self.config.focused_only && self.focused_output != Some(output);
+                if let Some(overlay) = self.overlay(output) {
+                    if filtered {
+                        // An overlay already visible, like a bar, still shows the new state.
+                        overlay.redraw();
+                    } else {
+                        overlay.popup(urgent);
+                    }
+                }
+            }
+            Event::Hide(id) => {
+                if let Some(overlay) = self.overlay(id) {
+                    overlay.hide();
+                }
+            }
+            Event::Configure { output, serial } => {
+                if let Some(overlay) = self.overlay(output) {
+                    overlay.configure(serial);
+                }
+            }
+            Event::Frame(id) => {
+                if let Some(overlay) = self.overlay(id) {
+                    overlay.frame();
+                }
+            }
+            Event::Closed(id) => {
+                if let Some(overlay) = self.overlay(id) {
+                    overlay.close();
+                }
+            }
+            Event::Click {
+                seat,
+                surface,
+                x,
+                y,
+                button,
+            } => {
+                let focused_output = self.focused_output;
+                let tag = self
+                    .overlays
+                    .iter()
+                    .find(|overlay| overlay.surface_id() == surface)
+                    .filter(|overlay| pointer::controls(focused_output, overlay.id()))
+                    .and_then(|overlay| overlay.tag_at(x, y));
+                let command = match button {
+                    pointer::BTN_LEFT => self.config.buttons.left,
+                    pointer::BTN_RIGHT => self.config.buttons.right,
+                    pointer::BTN_MIDDLE => self.config.buttons.middle,
+                    _ => return,
+                };
+                if let Some(tag) = tag {
+                    self.run_tag_command(&seat, command, 1 << tag);
+                }
+            }
+            Event::Scroll {
+                seat,
+                surface,
+                steps,
+            } => {
+                let skip_empty = self.config.skip_empty;
+                let focused_output = self.focused_output;
+                let overlay = match self
+                    .overlays
+                    .iter()
+                    .find(|overlay| overlay.surface_id() == surface)
+                    .filter(|overlay| pointer::controls(focused_output, overlay.id()))
+                {
+                    Some(overlay) => overlay,
+                    None => return,
+                };
+                let output = overlay.id();
+                let count = self.config.tags.count(overlay.tags.used());
+                if let Some(tag) = overlay.tags.cycle(count, steps, skip_empty) {
+                    self.run_tag_command(&seat, TagCommand::SetFocusedTags, 1 << tag);
+                }
+                // The overlay stays up while scrolling, even when the focus doesn't change.
+                self.handle(Event::Show {
+                    output,
+                    urgent: false,
+                });
+            }
+        }
+    }
 }
@@ -0,0 +1,585 @@
+use crate::cell::{Cell, Contents, Palette, Shade};
+use crate::config::{Anchors, Config, Margins};
+use crate::event::{self, Event};
+use crate::tags::TagState;
+use crate::wayland::river_status_unstable_v1::zriver_output_status_v1::ZriverOutputStatusV1;
+use crate::wayland::river_status_unstable_v1::zriver_status_manager_v1::ZriverStatusManagerV1;
+use crate::Env;
+use smithay_client_toolkit::environment::Environment;
+use smithay_client_toolkit::reexports::calloop::channel::Sender;
+use smithay_client_toolkit::reexports::calloop::timer::{Timeout, TimerHandle};
+use smithay_client_toolkit::shm::AutoMemPool;
+use snui::wayland::app;
+use snui::widgets::*;
+use snui::*;
+use std::io;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+use wayland_client::protocol::{wl_compositor::WlCompositor, wl_output::WlOutput, wl_shm::WlShm};
+use wayland_client::{Attached, Main};
+use wayland_protocols::wlr::unstable::layer_shell::v1::client::zwlr_layer_shell_v1::{
+    self, ZwlrLayerShellV1,
+};
+use wayland_protocols::wlr::unstable::layer_shell::v1::client::zwlr_layer_surface_v1;
+
+/// A fade or slide of the overlay in progress.
+#[derive(Clone, Copy)]
+struct Transition {
+    /// Whether the overlay is being shown, it's being hidden otherwise.
+    showing: bool,
+    start: Instant,
+}
+
+/// The overlay of an output.
+///
+/// Every overlay has its own widget, buffers and hide timeout
+/// so outputs don't interfere with each other.
+/// The layer surface lives as long as the output, it's only unmapped while the overlay is hidden.
+pub struct Overlay {
+    pub output: WlOutput,
+    pub output_status: Main<ZriverOutputStatusV1>,
+    pub tags: TagState,
+    /// The tag of every square of the strip.
+    shown: Vec<usize>,
+    app: app::Application<Border<Background<WidgetLayout>>>,
+    contents: Rc<Contents>,
+    pool: AutoMemPool,
+    config: Config,
+    sender: Sender<Event>,
+    compositor: Attached<WlCompositor>,
+    layer_shell: Attached<ZwlrLayerShellV1>,
+    layer: zwlr_layer_shell_v1::Layer,
+    anchor: zwlr_layer_surface_v1::Anchor,
+    margins: Margins,
+    /// Whether the layer surface was configured since it was last unmapped.
+    configured: bool,
+    timer: TimerHandle<u32>,
+    /// The pending hide of the overlay and when it's due while the overlay is visible.
+    hide: Option<(Timeout, Instant)>,
+    transition: Option<Transition>,
+    /// Whether a frame callback is pending, only one is requested at a time.
+    frame_pending: bool,
+}
+
+impl Overlay {
+    pub fn new(
+        env: &Environment<Env>,
+        config: &Config,
+        output: WlOutput,
+        sender: Sender<Event>,
+        timer: TimerHandle<u32>,
+        contents: Rc<Contents>,
+    ) -> io::Result<Overlay> {
+        let tags = TagState::default();
+        let shown = tags.shown(config.tags.count(0), config.hide_empty);
+        let widget = create_widget(config, Vec::new(), 1.);
+        let shm = env.require_global::<WlShm>();
+        let surface = env.create_surface();
+        // The overlay is rendered from the event loop, snui's own channel isn't used.
+        let (app, _) = app::Application::new(widget, surface.detach(), shm.detach());
+        let status_manager = env.require_global::<ZriverStatusManagerV1>();
+
+        let mut overlay = Overlay {
+            output_status: status_manager.get_river_output_status(&output),
+            output,
+            tags,
+            shown,
+            app,
+            contents,
+            pool: env.create_auto_pool()?,
+            config: config.clone(),
+            sender,
+            compositor: env.require_global::<WlCompositor>(),
+            layer_shell: env.require_global::<ZwlrLayerShellV1>(),
+            layer: config.layer.into(),
+            anchor: if config.bar && config.anchors == Anchors::default() {
+                // A bar needs an edge to reserve space along.
+                zwlr_layer_surface_v1::Anchor::Top
+            } else {
+                config.anchors.into()
+            },
+            margins: config.margins,
+            configured: false,
+            timer,
+            hide: None,
+            transition: None,
+            frame_pending: false,
+        };
+        overlay.paint();
+        overlay.assign_layer_surface();
+        Ok(overlay)
+    }
+
+    /// Identifies the overlay in the messages of the event loop.
+    pub fn id(&self) -> u32 {
+        self.output.as_ref().id()
+    }
+
+    pub fn surface_id(&self) -> u32 {
+        self.app.surface.as_ref().id()
+    }
+
+    /// The tag of the square at the given surface coordinates.
+    pub fn tag_at(&self, x: f64, y: f64) -> Option<usize> {
+        tag_at(&self.config, &self.shown, x, y)
+    }
+
+    /// Paints the squares after a change of the tags.
+    ///
+    /// The layer surface is resized when the tags shown change.
+    pub fn update(&mut self) {
+        let count = self.config.tags.count(self.tags.used());
+        let shown = self.tags.shown(count, self.config.hide_empty);
+        let resized = shown != self.shown;
+        self.shown = shown;
+        self.paint();
+        if resized {
+            self.resize();
+        }
+    }
+
+    /// Builds the strip in the colours of the tags, faded by the running transition.
+    fn paint(&mut self) {
+        let opacity = self.opacity();
+        let config = &self.config;
+        let cells = self
+            .shown
+            .iter()
+            .map(|&tag| {
+                let style = self.tags.style(tag);
+                let text = config.text_color(tag, style);
+                let icon = opacity * config.icons.opacity(style);
+                let palette = Palette {
+                    text: text.faded(opacity).0,
+                    badge: config.badge_color(tag).faded(opacity).0,
+                    icon: if config.icons.tint {
+                        Shade::Tint(text.faded(icon).0)
+                    } else {
+                        Shade::Opacity(icon)
+                    },
+                };
+                Cell::new(
+                    config.size,
+                    config.square_color(tag, style).faded(opacity).0,
+                    tag,
+                    self.tags.views[tag],
+                    self.contents.clone(),
+                    palette,
+                )
+            })
+            .collect();
+        self.app.widget = create_widget(&self.config, cells, opacity);
+    }
+
+    /// The eased progress of the transition for an effect lasting `duration` milliseconds.
+    fn progress(&self, duration: u64) -> f32 {
+        match self.transition {
+            Some(transition) if duration > 0 => {
+                let elapsed = transition.start.elapsed().as_millis() as f32;
+                self.config
+                    .animation
+                    .easing
+                    .apply((elapsed / duration as f32).min(1.))
+            }
+            _ => 1.,
+        }
+    }
+
+    fn opacity(&self) -> f32 {
+        match self.transition {
+            Some(Transition { showing: true, .. }) => self.progress(self.config.animation.fade_in),
+            Some(_) => 1. - self.progress(self.config.animation.fade_out),
+            None => 1.,
+        }
+    }
+
+    /// The margins of the layer surface, sliding it in from the edge it's anchored to.
+    fn margins(&self) -> Margins {
+        use zwlr_layer_surface_v1::Anchor;
+        let mut margins = self.margins;
+        if let Some(Transition { showing: true, .. }) = self.transition {
+            let hidden = 1. - self.progress(self.config.animation.slide_in);
+            let width = self.app.widget.get_width() as f32;
+            let height = self.app.widget.get_height() as f32;
+            let slide = |margin: &mut i32, extent: f32| {
+                *margin -= (hidden * (extent + *margin as f32)) as i32;
+            };
+            let anchor = self.anchor;
+            if anchor.contains(Anchor::Top) && !anchor.contains(Anchor::Bottom) {
+                slide(&mut margins.top, height);
+            } else if anchor.contains(Anchor::Bottom) && !anchor.contains(Anchor::Top) {
+                slide(&mut margins.bottom, height);
+            } else if anchor.contains(Anchor::Left) && !anchor.contains(Anchor::Right) {
+                slide(&mut margins.left, width);
+            } else if anchor.contains(Anchor::Right) && !anchor.contains(Anchor::Left) {
+                slide(&mut margins.right, width);
+            }
+        }
+        margins
+    }
+
+    /// Starts showing the overlay with the fade-in or the slide-in if there's one.
+    fn start_showing(&mut self) {
+        let animation = &self.config.animation;
+        self.transition = if animation.fade_in > 0 || animation.slide_in > 0 {
+            Some(Transition {
+                showing: true,
+                start: Instant::now(),
+            })
+        } else {
+            None
+        };
+    }
+
+    /// Whether the overlay should be mapped.
+    ///
+    /// A bar is always visible, a fading out overlay until the fade is over.
+    fn visible(&self) -> bool {
+        self.config.bar
+            || self.hide.is_some()
+            || matches!(self.transition, Some(Transition { showing: false, .. }))
+    }
+
+    /// Draws the overlay again if it's visible.
+    pub fn redraw(&mut self) {
+        if self.visible() && self.configured {
+            self.draw();
+        }
+    }
+
+    /// Renders the overlay at the current step of the transition.
+    fn draw(&mut self) {
+        self.paint();
+        self.set_margins();
+        if self.transition.is_some() {
+            self.request_frame();
+        }
+        self.app.render(&mut self.pool);
+        self.app.show();
+    }
+
+    fn set_margins(&self) {
+        if let Some(layer_surface) = self.app.layer_surface.as_ref() {
+            let margins = self.margins();
+            layer_surface.set_margin(margins.top, margins.right, margins.bottom, margins.left);
+        }
+    }
+
+    /// Asks the compositor when to draw the next step of the transition.
+    ///
+    /// The callback applies to the next commit of the surface.
+    fn request_frame(&mut self) {
+        if self.frame_pending {
+            return;
+        }
+        self.frame_pending = true;
+        let output = self.id();
+        let sender = self.sender.clone();
+        self.app.surface.frame().quick_assign(move |_, _, _| {
+            event::send(&sender, Event::Frame(output));
+        });
+    }
+
+    /// Draws the next step of the transition, the overlay is unmapped after fading out.
+    pub fn frame(&mut self) {
+        self.frame_pending = false;
+        let transition = match self.transition {
+            Some(transition) if self.configured => transition,
+            _ => return,
+        };
+        let animation = &self.config.animation;
+        let duration = if transition.showing {
+            animation.fade_in.max(animation.slide_in)
+        } else {
+            animation.fade_out
+        };
+        if transition.start.elapsed() >= Duration::from_millis(duration) {
+            self.transition = None;
+            if !transition.showing {
+                self.unmap();
+                return;
+            }
+        }
+        self.draw();
+    }
+
+    pub fn configure(&mut self, serial: u32) {
+        if let Some(layer_surface) = self.app.layer_surface.as_ref() {
+            layer_surface.ack_configure(serial);
+            self.configured = true;
+            if self.visible() {
+                // The first frame is drawn at the start of the transition.
+                self.paint();
+                self.set_margins();
+                self.app.init(&mut self.pool);
+                if self.transition.is_some() {
+                    self.request_frame();
+                    self.app.surface.commit();
+                }
+            }
+        }
+    }
+
+    /// Hides the overlay once its timeout expired, fading it out if configured.
+    pub fn hide(&mut self) {
+        if self.hide.take().is_some() {
+            if self.config.animation.fade_out > 0 && self.configured {
+                self.transition = Some(Transition {
+                    showing: false,
+                    start: Instant::now(),
+                });
+                self.draw();
+            } else {
+                self.unmap();
+            }
+        }
+    }
+
+    /// Drops the layer surface closed by the compositor.
+    ///
+    /// A new one is created the next time the overlay is shown.
+    pub fn close(&mut self) {
+        if let Some((timeout, _)) = self.hide.take() {
+            self.timer.cancel_timeout(&timeout);
+        }
+        self.transition = None;
+        self.destroy_layer_surface();
+    }
+
+    /// Shows the overlay until the timeout, or the urgent one, expires.
+    ///
+    /// Showing it again while it's visible postpones the hide unless `extend` is unset,
+    /// a later hide is never brought forward. A bar is only drawn again.
+    /// A fade-out in progress is cancelled and the current tags are shown at once.
+    pub fn popup(&mut self, urgent: bool) {
+        let timeout = Duration::from_millis(if urgent {
+            self.config.urgent_timeout
+        } else {
+            self.config.timeout
+        });
+        let deadline = Instant::now() + timeout;
+        let visible = self.visible();
+        if let Some((pending, due)) = self.hide.take() {
+            if self.config.extend && due < deadline {
+                self.timer.cancel_timeout(&pending);
+            } else {
+                self.hide = Some((pending, due));
+            }
+        }
+        if let Some(Transition { showing: false, .. }) = self.transition {
+            self.transition = None;
+        }
+        if self.app.layer_surface.is_none() {
+            // The compositor closed the previous one.
+            // The enter and leave events go to a filter like the ones of the surfaces
+            // created by the environment instead of the orphan fallback.
+            let surface = self.compositor.create_surface();
+            surface.quick_assign(|_, _, _| {});
+            self.app.surface = surface.detach();
+            self.start_showing();
+            self.assign_layer_surface();
+        } else if self.configured {
+            // The first configure arrives while the overlay is hidden.
+            if !visible {
+                self.start_showing();
+            }
+            self.draw();
+        } else if !self.config.bar {
+            // An unmapped layer surface is mapped again by committing it without a buffer
+            // and drawing once it's configured.
+            self.start_showing();
+            self.app.surface.commit();
+        }
+        if !self.config.bar && self.hide.is_none() {
+            self.hide = Some((self.timer.add_timeout(timeout, self.id()), deadline));
+        }
+    }
+
+    /// Gives the layer surface the size of the widget.
+    ///
+    /// A visible overlay is drawn again once the compositor configures the new size.
+    fn resize(&mut self) {
+        if let Some(layer_surface) = self.app.layer_surface.as_ref() {
+            layer_surface.set_size(self.app.widget.get_width(), self.app.widget.get_height());
+            if self.config.bar {
+                layer_surface.set_exclusive_zone(self.exclusive_zone());
+            }
+            if self.visible() {
+                self.app.surface.commit();
+            }
+        }
+    }
+
+    /// The space a bar reserves along the edge it's anchored to.
+    fn exclusive_zone(&self) -> i32 {
+        use zwlr_layer_surface_v1::Anchor;
+        // A bar stretched along the top edge is also anchored to the left and right ones.
+        if self.anchor.contains(Anchor::Top) != self.anchor.contains(Anchor::Bottom) {
+            self.app.widget.get_height() as i32
+        } else {
+            self.app.widget.get_width() as i32
+        }
+    }
+
+    /// Hides the overlay by attaching a null buffer to its surface.
+    ///
+    /// This unmaps the layer surface, it has to be configured again before it's shown.
+    /// The frame callbacks of an unmapped surface never fire, so none is left pending.
+    fn unmap(&mut self) {
+        self.app.surface.attach(None, 0, 0);
+        self.app.surface.commit();
+        self.configured = false;
+        self.frame_pending = false;
+        self.transition = None;
+    }
+
+    /// Gives the surface of the application the role of a layer surface.
+    fn assign_layer_surface(&mut self) {
+        let surface = &self.app.surface;
+        let layer_surface = self.layer_shell.get_layer_surface(
+            surface,
+            Some(&self.output),
+            self.layer,
+            self.config.namespace.clone(),
+        );
+        layer_surface.set_size(self.app.widget.get_width(), self.app.widget.get_height());
+        layer_surface.set_anchor(self.anchor);
+        let margins = self.margins();
+        layer_surface.set_margin(margins.top, margins.right, margins.bottom, margins.left);
+        layer_surface
+            .set_keyboard_interactivity(zwlr_layer_surface_v1::KeyboardInteractivity::None);
+        if self.config.bar {
+            layer_surface.set_exclusive_zone(self.exclusive_zone());
+        }
+        if !self.config.pointer {
+            // An empty input region lets the clicks through to the windows below.
+            let region = self.compositor.create_region();
+            surface.set_input_region(Some(&region));
+            region.destroy();
+        }
+
+        // The events are handled by the overlay so they're ordered with its hide timeout.
+        let output = self.id();
+        let sender = self.sender.clone();
+        layer_surface.quick_assign(move |_, event, _| {
+            let event = match event {
+                zwlr_layer_surface_v1::Event::Configure { serial, .. } => {
+                    Event::Configure { output, serial }
+                }
+                zwlr_layer_surface_v1::Event::Closed => Event::Closed(output),
+                _ => return,
+            };
+            event::send(&sender, event);
+        });
+
+        surface.commit();
+        self.app.layer_surface = Some(layer_surface.detach());
+        self.configured = false;
+        self.frame_pending = false;
+    }
+
+    fn destroy_layer_surface(&mut self) {
+        if let Some(layer_surface) = self.app.layer_surface.take() {
+            layer_surface.destroy();
+            self.app.surface.destroy();
+        }
+        self.configured = false;
+    }
+
+    /// Releases the protocol objects of the overlay and its surface.
+    pub fn destroy(&mut self) {
+        self.output_status.destroy();
+        if let Some((timeout, _)) = self.hide.take() {
+            self.timer.cancel_timeout(&timeout);
+        }
+        self.destroy_layer_surface();
+    }
+}
+
+/// The tag of the square at the given coordinates of a strip showing the tags of `shown`.
+///
+/// The border, the padding and the gaps between the squares belong to no tag.
+pub fn tag_at(config: &Config, shown: &[usize], x: f64, y: f64) -> Option<usize> {
+    let offset = (config.border + config.padding) as f64;
+    let (x, y) = (x - offset, y - offset);
+    let step = (config.size + config.spacing) as f64;
+    if x < 0. || y < 0. || y >= config.size as f64 || x % step >= config.size as f64 {
+        return None;
+    }
+    shown.get((x / step) as usize).copied()
+}
+
+/// Builds the strip out of the squares of the tags with its colours faded by `opacity`.
+pub fn create_widget(
+    config: &Config,
+    cells: Vec<Cell>,
+    opacity: f32,
+) -> Border<Background<WidgetLayout>> {
+    let colors = &config.colors;
+    let mut tags = WidgetLayout::horizontal(config.spacing);
+
+    for cell in cells {
+        tags.add(cell).unwrap();
+    }
+
+    boxed(
+        tags,
+        config.padding,
+        config.border,
+        colors.background.faded(opacity).0,
+        colors.border.faded(opacity).0,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A strip of 40 pixel squares 10 pixels apart, starting 11 pixels from the edges.
+    fn strip() -> Config {
+        Config {
+            size: 40,
+            spacing: 10,
+            padding: 10,
+            border: 1,
+            ..Config::default()
+        }
+    }
+
+    #[test]
+    fn squares_are_hit() {
+        let config = strip();
+        let shown: Vec<usize> = (0..9).collect();
+        assert_eq!(tag_at(&config, &shown, 11., 11.), Some(0));
+        assert_eq!(tag_at(&config, &shown, 50.9, 50.9), Some(0));
+        assert_eq!(tag_at(&config, &shown, 61., 30.), Some(1));
+        assert_eq!(tag_at(&config, &shown, 11. + 8. * 50. + 39., 11.), Some(8));
+    }
+
+    #[test]
+    fn border_padding_and_gaps_are_missed() {
+        let config = strip();
+        let shown: Vec<usize> = (0..9).collect();
+        assert_eq!(tag_at(&config, &shown, 0., 20.), None);
+        assert_eq!(tag_at(&config, &shown, 10.9, 20.), None);
+        assert_eq!(tag_at(&config, &shown, 20., 10.9), None);
+        assert_eq!(tag_at(&config, &shown, 20., 51.), None);
+        assert_eq!(tag_at(&config, &shown, 51., 20.), None);
+        assert_eq!(tag_at(&config, &shown, 60.9, 20.), None);
+        // Beyond the last square.
+        assert_eq!(tag_at(&config, &shown, 11. + 9. * 50., 20.), None);
+    }
+
+    #[test]
+    fn squares_map_to_the_shown_tags() {
+        let config = Config {
+            hide_empty: true,
+            ..strip()
+        };
+        let shown = vec![1, 3, 20];
+        assert_eq!(tag_at(&config, &shown, 20., 20.), Some(1));
+        assert_eq!(tag_at(&config, &shown, 70., 20.), Some(3));
+        assert_eq!(tag_at(&config, &shown, 120., 20.), Some(20));
+        assert_eq!(tag_at(&config, &shown, 170., 20.), None);
+    }
+}
@@ -0,0 +1,188 @@
+use crate::event::{self, Event};
+use smithay_client_toolkit::reexports::calloop::channel::Sender;
+use wayland_client::protocol::wl_pointer::{self, WlPointer};
+use wayland_client::protocol::wl_seat::WlSeat;
+use wayland_client::Main;
+
+/// Linux input event codes of the buttons.
+pub const BTN_LEFT: u32 = 0x110;
+pub const BTN_RIGHT: u32 = 0x111;
+pub const BTN_MIDDLE: u32 = 0x112;
+
+/// Scroll distance of a wheel notch, as sent by wlroots.
+const SCROLL_STEP: f64 = 15.;
+
+/// Turns the vertical axis events of a pointer into steps.
+///
+/// Wheels send discrete steps from `wl_seat` version 5, they're followed by an `axis`
+/// event carrying the same scroll which is skipped. The distance of continuous scrolling,
+/// on touchpads for example, is accumulated until it reaches a notch.
+#[derive(Debug, Default)]
+struct Scroll {
+    distance: f64,
+    discrete: bool,
+}
+
+impl Scroll {
+    fn discrete(&mut self, steps: i32) -> i32 {
+        self.distance = 0.;
+        self.discrete = true;
+        steps
+    }
+
+    fn axis(&mut self, value: f64) -> i32 {
+        if self.discrete {
+            self.discrete = false;
+            return 0;
+        }
+        self.distance += value;
+        let steps = (self.distance / SCROLL_STEP) as i32;
+        self.distance -= steps as f64 * SCROLL_STEP;
+        steps
+    }
+
+    /// Forgets the scroll shorter than a notch, when the fingers are lifted for example.
+    fn stop(&mut self) {
+        *self = Scroll::default();
+    }
+}
+
+/// Forwards the clicks and scrolls on the overlays to the event loop.
+///
+/// The overlays are the only surfaces of the client so the pointer is always over one of them.
+pub fn assign(pointer: &Main<WlPointer>, seat: WlSeat, sender: Sender<Event>) {
+    let mut surface = None;
+    let mut position = (0., 0.);
+    let mut scroll = Scroll::default();
+    pointer.quick_assign(move |_, event, _| {
+        let event = match event {
+            wl_pointer::Event::Enter {
+                surface: entered,
+                surface_x,
+                surface_y,
+                ..
+            } => {
+                surface = Some(entered.as_ref().id());
+                position = (surface_x, surface_y);
+                return;
+            }
+            wl_pointer::Event::Leave { .. } => {
+                surface = None;
+                scroll.stop();
+                return;
+            }
+            wl_pointer::Event::Motion {
+                surface_x,
+                surface_y,
+                ..
+            } => {
+                position = (surface_x, surface_y);
+                return;
+            }
+            wl_pointer::Event::Button {
+                button,
+                state: wl_pointer::ButtonState::Pressed,
+                ..
+            } => match surface {
+                Some(surface) => Event::Click {
+                    seat: seat.clone(),
+                    surface,
+                    x: position.0,
+                    y: position.1,
+                    button,
+                },
+                None => return,
+            },
+            wl_pointer::Event::AxisDiscrete {
+                axis: wl_pointer::Axis::VerticalScroll,
+                discrete,
+            } => match scrolled(&seat, surface, scroll.discrete(discrete)) {
+                Some(event) => event,
+                None => return,
+            },
+            wl_pointer::Event::Axis {
+                axis: wl_pointer::Axis::VerticalScroll,
+                value,
+                ..
+            } => match scrolled(&seat, surface, scroll.axis(value)) {
+                Some(event) => event,
+                None => return,
+            },
+            wl_pointer::Event::AxisStop {
+                axis: wl_pointer::Axis::VerticalScroll,
+                ..
+            } => {
+                scroll.stop();
+                return;
+            }
+            _ => return,
+        };
+        event::send(&sender, event);
+    });
+}
+
+/// Whether the commands run from the overlay of `output` act on the tags it shows.
+///
+/// River runs them on the output focused by the seat, which isn't always the one under
+/// the pointer, so the input over the other overlays is ignored.
+pub fn controls(focused_output: Option<u32>, output: u32) -> bool {
+    focused_output == Some(output)
+}
+
+/// The event of scrolling `steps` over `surface`, if it moves the focus.
+fn scrolled(seat: &WlSeat, surface: Option<u32>, steps: i32) -> Option<Event> {
+    match surface {
+        Some(surface) if steps != 0 => Some(Event::Scroll {
+            seat: seat.clone(),
+            surface,
+            steps,
+        }),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_the_focused_output_is_controlled() {
+        assert!(controls(Some(3), 3));
+        assert!(!controls(Some(4), 3));
+        // Until river tells which output is focused, a command could act on any of them.
+        assert!(!controls(None, 3));
+    }
+
+    #[test]
+    fn wlroots_notches_are_single_steps() {
+        let mut scroll = Scroll::default();
+        let steps: Vec<_> = (0..4).map(|_| scroll.axis(SCROLL_STEP)).collect();
+        assert_eq!(steps, vec![1; 4]);
+        assert_eq!(scroll.axis(-SCROLL_STEP * 2.), -2);
+    }
+
+    #[test]
+    fn discrete_steps_skip_their_axis_event() {
+        let mut scroll = Scroll::default();
+        assert_eq!(scroll.discrete(1), 1);
+        assert_eq!(scroll.axis(10.), 0);
+        assert_eq!(scroll.discrete(-2), -2);
+        assert_eq!(scroll.axis(-30.), 0);
+        // Continuous scrolling on the same pointer is still accumulated.
+        assert_eq!(scroll.axis(10.), 0);
+        assert_eq!(scroll.axis(10.), 1);
+    }
+
+    #[test]
+    fn continuous_scroll_accumulates_until_stopped() {
+        let mut scroll = Scroll::default();
+        assert_eq!(scroll.axis(6.), 0);
+        assert_eq!(scroll.axis(6.), 0);
+        assert_eq!(scroll.axis(6.), 1);
+        assert_eq!(scroll.axis(6.), 0);
+        scroll.stop();
+        assert_eq!(scroll.axis(6.), 0);
+        assert_eq!(scroll.axis(-6.), 0);
+        assert_eq!(scroll.axis(-15.), -1);
+    }
+}
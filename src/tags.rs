@@ -0,0 +1,350 @@
+/// The tags of an output as reported by river.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TagState {
+    pub focused: u32,
+    /// Tags holding at least one view.
+    pub occupied: u32,
+    pub urgent: u32,
+    /// Number of views on each tag.
+    pub views: [u32; 32],
+    /// The focused tags before the last `focused_tags` event.
+    pub previous: u32,
+    /// Whether river sent the focused tags yet.
+    pub focus_known: bool,
+    /// Whether river sent the urgent tags yet.
+    pub urgent_known: bool,
+}
+
+/// How the cell of a tag is drawn.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Style {
+    Inactive,
+    Occupied,
+    Urgent,
+    Focused,
+}
+
+impl TagState {
+    pub fn set_focused(&mut self, tags: u32) {
+        self.previous = self.focused;
+        self.focused = tags;
+        self.focus_known = true;
+    }
+
+    /// Whether the last `focused_tags` event switched tags.
+    pub fn focus_changed(&self) -> bool {
+        self.focused != self.previous
+    }
+
+    /// Updates the occupancy from the `view_tags` array.
+    ///
+    /// Every element of the array is the tag bitfield of a view.
+    pub fn set_view_tags(&mut self, array: &[u8]) {
+        self.occupied = 0;
+        self.views = [0; 32];
+        for view in array.chunks_exact(4) {
+            let tags = u32::from_ne_bytes([view[0], view[1], view[2], view[3]]);
+            self.occupied |= tags;
+            for (i, count) in self.views.iter_mut().enumerate() {
+                if tags & 1 << i != 0 {
+                    *count += 1;
+                }
+            }
+        }
+    }
+
+    /// Sets the urgent tags and returns the ones which just became urgent.
+    pub fn set_urgent(&mut self, tags: u32) -> u32 {
+        let raised = tags & !self.urgent;
+        self.urgent = tags;
+        self.urgent_known = true;
+        raised
+    }
+
+    /// The style of the tag at `index`, counting from 0.
+    ///
+    /// A focused tag takes precedence over an urgent one
+    /// which itself takes precedence over an occupied one.
+    pub fn style(&self, index: usize) -> Style {
+        let mask = 1u32.checked_shl(index as u32).unwrap_or(0);
+        if self.focused & mask != 0 {
+            Style::Focused
+        } else if self.urgent & mask != 0 {
+            Style::Urgent
+        } else if self.occupied & mask != 0 {
+            Style::Occupied
+        } else {
+            Style::Inactive
+        }
+    }
+
+    /// Tags which are focused, occupied or urgent.
+    pub fn used(&self) -> u32 {
+        self.focused | self.occupied | self.urgent
+    }
+
+    /// The indices of the tags shown among the first `count`.
+    ///
+    /// With `hide_empty` only the used tags are kept, the first tag stands in
+    /// until river sent the state of the output.
+    pub fn shown(&self, count: u32, hide_empty: bool) -> Vec<usize> {
+        let used = self.used();
+        let mut shown: Vec<usize> = (0..count.min(32) as usize)
+            .filter(|i| !hide_empty || used & 1 << i != 0)
+            .collect();
+        if shown.is_empty() && count > 0 {
+            shown.push(0);
+        }
+        shown
+    }
+
+    /// The tag reached by moving the focus `steps` tags away among the first `count`.
+    ///
+    /// The focus wraps around and starts from the lowest focused tag.
+    /// With `skip_empty` only the occupied tags are reached.
+    pub fn cycle(&self, count: u32, steps: i32, skip_empty: bool) -> Option<usize> {
+        let count = count.min(32) as usize;
+        if count == 0 {
+            return None;
+        }
+        let start = (self.focused.trailing_zeros() as usize).min(count - 1);
+        let mut tag = start;
+        for _ in 0..steps.unsigned_abs() {
+            loop {
+                tag = if steps > 0 {
+                    (tag + 1) % count
+                } else {
+                    (tag + count - 1) % count
+                };
+                if !skip_empty || self.occupied & 1 << tag != 0 || tag == start {
+                    break;
+                }
+            }
+        }
+        Some(tag)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Encodes the bitfields of views like river's `view_tags` array.
+    fn view_tags(views: &[u32]) -> Vec<u8> {
+        views.iter().flat_map(|tags| tags.to_ne_bytes()).collect()
+    }
+
+    /// The styles of the first `count` tags.
+    fn styles(state: &TagState, count: usize) -> Vec<Style> {
+        (0..count).map(|i| state.style(i)).collect()
+    }
+
+    #[test]
+    fn default_is_inactive() {
+        let state = TagState::default();
+        assert_eq!(styles(&state, 9), vec![Style::Inactive; 9]);
+    }
+
+    #[test]
+    fn single_focused_tag() {
+        let mut state = TagState::default();
+        state.set_focused(1 << 2);
+        let cells = styles(&state, 9);
+        assert_eq!(cells[2], Style::Focused);
+        assert_eq!(cells.iter().filter(|s| **s == Style::Focused).count(), 1);
+    }
+
+    #[test]
+    fn multiple_focused_tags() {
+        let mut state = TagState::default();
+        state.set_focused(0b1_0000_0101);
+        assert_eq!(
+            styles(&state, 9),
+            vec![
+                Style::Focused,
+                Style::Inactive,
+                Style::Focused,
+                Style::Inactive,
+                Style::Inactive,
+                Style::Inactive,
+                Style::Inactive,
+                Style::Inactive,
+                Style::Focused,
+            ]
+        );
+    }
+
+    #[test]
+    fn focused_over_urgent_over_occupied() {
+        let mut state = TagState::default();
+        state.set_view_tags(&view_tags(&[0b111]));
+        state.set_urgent(0b110);
+        state.set_focused(0b100);
+        assert_eq!(
+            styles(&state, 4),
+            vec![
+                Style::Occupied,
+                Style::Urgent,
+                Style::Focused,
+                Style::Inactive
+            ]
+        );
+    }
+
+    #[test]
+    fn view_tags_count_views() {
+        let mut state = TagState::default();
+        state.set_view_tags(&view_tags(&[0b1, 0b11, 1 << 31]));
+        assert_eq!(state.occupied, 0b11 | 1 << 31);
+        assert_eq!(state.views[0], 2);
+        assert_eq!(state.views[1], 1);
+        assert_eq!(state.views[2], 0);
+        assert_eq!(state.views[31], 1);
+    }
+
+    #[test]
+    fn view_tags_replace_occupancy() {
+        let mut state = TagState::default();
+        state.set_view_tags(&view_tags(&[0b1, 0b10]));
+        state.set_view_tags(&view_tags(&[0b100]));
+        assert_eq!(state.occupied, 0b100);
+        assert_eq!(state.views[0], 0);
+        assert_eq!(state.views[2], 1);
+        state.set_view_tags(&[]);
+        assert_eq!(state.occupied, 0);
+        assert_eq!(state.views, [0; 32]);
+    }
+
+    #[test]
+    fn view_tags_ignore_trailing_bytes() {
+        let mut state = TagState::default();
+        let mut array = view_tags(&[0b1]);
+        array.push(0xff);
+        state.set_view_tags(&array);
+        assert_eq!(state.occupied, 0b1);
+    }
+
+    #[test]
+    fn tags_above_nine() {
+        let mut state = TagState::default();
+        state.set_focused(1 << 9);
+        state.set_urgent(1 << 12);
+        state.set_view_tags(&view_tags(&[1 << 15]));
+        let cells = styles(&state, 16);
+        assert_eq!(cells[9], Style::Focused);
+        assert_eq!(cells[12], Style::Urgent);
+        assert_eq!(cells[15], Style::Occupied);
+        // Only the displayed cells are styled.
+        assert_eq!(styles(&state, 9), vec![Style::Inactive; 9]);
+    }
+
+    #[test]
+    fn all_32_tags() {
+        let mut state = TagState::default();
+        state.set_focused(u32::MAX);
+        assert_eq!(styles(&state, 32), vec![Style::Focused; 32]);
+
+        state.set_focused(1 << 31);
+        state.set_urgent(1 << 30);
+        state.set_view_tags(&view_tags(&[u32::MAX]));
+        let cells = styles(&state, 32);
+        assert_eq!(cells[31], Style::Focused);
+        assert_eq!(cells[30], Style::Urgent);
+        assert!(cells[..30].iter().all(|s| *s == Style::Occupied));
+        assert_eq!(state.views, [1; 32]);
+    }
+
+    #[test]
+    fn tags_beyond_32_are_inactive() {
+        let mut state = TagState::default();
+        state.set_focused(u32::MAX);
+        assert_eq!(state.style(32), Style::Inactive);
+        assert_eq!(state.style(40), Style::Inactive);
+    }
+
+    #[test]
+    fn previous_focus() {
+        let mut state = TagState::default();
+        assert!(!state.focus_known);
+        state.set_focused(0b1);
+        assert!(state.focus_known);
+        assert!(state.focus_changed());
+        state.set_focused(0b10);
+        assert_eq!(state.previous, 0b1);
+        assert!(state.focus_changed());
+        state.set_focused(0b10);
+        assert_eq!(state.previous, 0b10);
+        assert!(!state.focus_changed());
+    }
+
+    #[test]
+    fn shown_tags() {
+        let mut state = TagState::default();
+        state.set_focused(0b10);
+        state.set_urgent(1 << 20);
+        state.set_view_tags(&view_tags(&[0b1000, 1 << 31]));
+        assert_eq!(state.shown(4, false), vec![0, 1, 2, 3]);
+        assert_eq!(state.shown(9, true), vec![1, 3]);
+        assert_eq!(state.shown(32, true), vec![1, 3, 20, 31]);
+        assert_eq!(state.shown(40, false).len(), 32);
+    }
+
+    #[test]
+    fn first_tag_stands_in_for_an_empty_output() {
+        let state = TagState::default();
+        assert_eq!(state.shown(9, true), vec![0]);
+        assert_eq!(state.shown(0, true), Vec::<usize>::new());
+    }
+
+    #[test]
+    fn cycle_wraps_around() {
+        let mut state = TagState::default();
+        state.set_focused(1 << 8);
+        assert_eq!(state.cycle(9, 1, false), Some(0));
+        assert_eq!(state.cycle(9, -1, false), Some(7));
+        assert_eq!(state.cycle(9, 10, false), Some(0));
+        state.set_focused(0b1);
+        assert_eq!(state.cycle(9, -1, false), Some(8));
+        assert_eq!(state.cycle(32, -1, false), Some(31));
+    }
+
+    #[test]
+    fn cycle_starts_from_the_lowest_focused_tag() {
+        let mut state = TagState::default();
+        state.set_focused(0b1100);
+        assert_eq!(state.cycle(9, 1, false), Some(3));
+        assert_eq!(state.cycle(9, -1, false), Some(1));
+    }
+
+    #[test]
+    fn cycle_skips_empty_tags() {
+        let mut state = TagState::default();
+        state.set_focused(0b1);
+        state.set_view_tags(&view_tags(&[0b10000, 1 << 20]));
+        assert_eq!(state.cycle(32, 1, true), Some(4));
+        assert_eq!(state.cycle(32, 2, true), Some(20));
+        assert_eq!(state.cycle(32, 3, true), Some(0));
+        assert_eq!(state.cycle(32, -1, true), Some(20));
+        // Tags beyond the strip are out of reach.
+        assert_eq!(state.cycle(9, 2, true), Some(0));
+    }
+
+    #[test]
+    fn cycle_without_other_occupied_tags() {
+        let mut state = TagState::default();
+        state.set_focused(0b100);
+        assert_eq!(state.cycle(9, 1, true), Some(2));
+        assert_eq!(state.cycle(0, 1, false), None);
+    }
+
+    #[test]
+    fn only_raised_urgent_tags_are_returned() {
+        let mut state = TagState::default();
+        assert!(!state.urgent_known);
+        assert_eq!(state.set_urgent(0b11), 0b11);
+        assert!(state.urgent_known);
+        assert_eq!(state.set_urgent(0b111), 0b100);
+        assert_eq!(state.set_urgent(0b1), 0);
+        assert_eq!(state.urgent, 0b1);
+    }
+}
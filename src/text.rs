@@ -0,0 +1,75 @@
+use fontconfig::Fontconfig;
+use std::fs;
+
+/// Coverage of a rasterized text, one byte per pixel.
+pub struct Mask {
+    pub width: u32,
+    pub height: u32,
+    pub coverage: Vec<u8>,
+}
+
+pub struct Font {
+    font: fontdue::Font,
+    size: f32,
+}
+
+impl Font {
+    /// Loads the font matching a fontconfig pattern, `<family>[:<style>]`.
+    pub fn load(pattern: &str, size: f32) -> Result<Font, String> {
+        let (family, style) = match pattern.split_once(':') {
+            Some((family, style)) => (family, Some(style)),
+            None => (pattern, None),
+        };
+        let fontconfig =
+            Fontconfig::new().ok_or_else(|| "failed to initialize fontconfig".to_owned())?;
+        let found = fontconfig
+            .find(family, style)
+            .ok_or_else(|| format!("no font matches \"{}\"", pattern))?;
+        let data = fs::read(&found.path).map_err(|e| format!("{}: {}", found.path.display(), e))?;
+        let font = fontdue::Font::from_bytes(data, fontdue::FontSettings::default())
+            .map_err(|e| format!("{}: {}", found.path.display(), e))?;
+        Ok(Font { font, size })
+    }
+
+    /// Rasterizes a line of text.
+    pub fn rasterize(&self, text: &str) -> Mask {
+        let (ascent, descent) = match self.font.horizontal_line_metrics(self.size) {
+            Some(metrics) => (metrics.ascent, metrics.descent),
+            None => (self.size, 0.),
+        };
+        let glyphs: Vec<_> = text
+            .chars()
+            .map(|c| self.font.rasterize(c, self.size))
+            .collect();
+        let width = glyphs
+            .iter()
+            .map(|(metrics, _)| metrics.advance_width)
+            .sum::<f32>()
+            .ceil() as u32;
+        let height = (ascent - descent).ceil() as u32;
+        let mut mask = Mask {
+            width,
+            height,
+            coverage: vec![0; (width * height) as usize],
+        };
+
+        let mut pen = 0.;
+        for (metrics, bitmap) in &glyphs {
+            let left = (pen + metrics.xmin as f32).round() as i32;
+            // Glyphs are placed on the baseline, `ymin` is the offset of their bottom edge.
+            let top = (ascent - (metrics.ymin + metrics.height as i32) as f32).round() as i32;
+            for row in 0..metrics.height {
+                for col in 0..metrics.width {
+                    let (x, y) = (left + col as i32, top + row as i32);
+                    if x < 0 || y < 0 || x >= width as i32 || y >= height as i32 {
+                        continue;
+                    }
+                    let pixel = &mut mask.coverage[(y as u32 * width + x as u32) as usize];
+                    *pixel = (*pixel).max(bitmap[row * metrics.width + col]);
+                }
+            }
+            pen += metrics.advance_width;
+        }
+        mask
+    }
+}
@@ -7,6 +7,7 @@ extern crate wayland_protocols;
 // and avoid exposing internal details.
 //
 // You can use all the types from my_protocol as if they went from `wayland_client::protocol`.
+pub use control::client as river_control_unstable_v1;
 pub use wayland::client as river_status_unstable_v1;
 
 pub mod wayland {
@@ -36,3 +37,25 @@ pub mod wayland {
         ));
     }
 }
+
+// Every protocol needs its own module since the generated code declares module level statics.
+pub mod control {
+    #![allow(dead_code, non_camel_case_types, unused_unsafe, unused_variables)]
+    #![allow(non_upper_case_globals, non_snake_case, unused_imports)]
+
+    pub mod client {
+        pub(crate) use wayland_client::protocol::wl_seat;
+        pub(crate) use wayland_client::{protocol, sys};
+        pub(crate) use wayland_client::{
+            AnonymousObject, Attached, Display, GlobalManager, Main, Proxy, ProxyMap,
+        };
+        pub(crate) use wayland_commons::map::{Object, ObjectMetadata};
+        pub(crate) use wayland_commons::smallvec;
+        pub(crate) use wayland_commons::wire::{Argument, ArgumentType, Message, MessageDesc};
+        pub(crate) use wayland_commons::{Interface, MessageGroup};
+        include!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/src/wayland/river_control_unstable_v1.rs"
+        ));
+    }
+}